@@ -16,11 +16,12 @@ class BottomNav extends StatelessWidget {
     final session = SessionStore.I.session.value;
     final role = session?.role ?? '';
     final isLogged = session != null;
+    final isOrganizer = role == 'organizer' || role == 'organisateur';
 
     List<_NavItem> items = [
       _NavItem('home', l10n.navHome, Icons.home, onTap: () => Navigator.pushReplacementNamed(context, '/')),
     ];
-    if (!isLogged || role != 'organisateur') {
+    if (!isLogged || !isOrganizer) {
       items.add(
         _NavItem('tickets', l10n.navTickets, Icons.confirmation_num,
             onTap: () => Navigator.pushReplacementNamed(context, '/tickets')),
@@ -32,7 +33,7 @@ class BottomNav extends StatelessWidget {
             onTap: () => Navigator.pushReplacementNamed(context, '/market')),
       );
     }
-    if (isLogged && role == 'organisateur') {
+    if (isLogged && isOrganizer) {
       items.add(
         _NavItem('orga', l10n.navOrga, Icons.event,
             onTap: () => Navigator.pushReplacementNamed(context, '/orga')),
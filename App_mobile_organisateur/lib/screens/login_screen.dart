@@ -55,7 +55,7 @@ class _LoginScreenState extends State<LoginScreen> {
 
   bool _ensureOrganizerSession() {
     final role = SessionStore.I.session.value?.role ?? '';
-    if (role == 'organisateur') return true;
+    if (role == 'organizer' || role == 'organisateur') return true;
     SessionStore.I.clear();
     _showStyledSnack(context, 'Compte non-organisateur', bg: const Color(0xFFB00020));
     return false;
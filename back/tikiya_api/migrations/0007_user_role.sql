@@ -0,0 +1,15 @@
+-- Typed roles: users.role becomes the user_role enum (client, organizer, admin, scanner)
+DO $$
+BEGIN
+    CREATE TYPE user_role AS ENUM ('client', 'organizer', 'admin', 'scanner');
+EXCEPTION
+    WHEN duplicate_object THEN NULL;
+END $$;
+
+-- Normalise legacy free-form values before the cast.
+UPDATE users SET role = 'organizer' WHERE role = 'organisateur';
+UPDATE users SET role = 'client' WHERE role NOT IN ('client', 'organizer', 'admin', 'scanner');
+
+ALTER TABLE users ALTER COLUMN role DROP DEFAULT;
+ALTER TABLE users ALTER COLUMN role TYPE user_role USING role::user_role;
+ALTER TABLE users ALTER COLUMN role SET DEFAULT 'client';
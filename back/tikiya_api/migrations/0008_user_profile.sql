@@ -0,0 +1,7 @@
+-- Profile fields captured at registration and editable via PATCH /me
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS first_name TEXT NULL,
+    ADD COLUMN IF NOT EXISTS last_name TEXT NULL,
+    ADD COLUMN IF NOT EXISTS phone TEXT NULL,
+    ADD COLUMN IF NOT EXISTS preferred_language TEXT NULL,
+    ADD COLUMN IF NOT EXISTS city TEXT NULL;
@@ -0,0 +1,6 @@
+-- Device metadata on sessions (GET /me/sessions)
+ALTER TABLE sessions
+    ADD COLUMN IF NOT EXISTS user_agent TEXT NULL,
+    ADD COLUMN IF NOT EXISTS ip TEXT NULL,
+    ADD COLUMN IF NOT EXISTS device_label TEXT NULL,
+    ADD COLUMN IF NOT EXISTS last_used_at TIMESTAMPTZ NULL;
@@ -0,0 +1,13 @@
+-- Refresh token families: a session is one family; every rotated-out secret hash is kept so that
+-- replaying a superseded refresh token can be detected and the whole family revoked.
+CREATE TABLE IF NOT EXISTS session_secret_history (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    session_id UUID NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL,
+    superseded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_session_secret_history_lookup ON session_secret_history (session_id, token_hash);
+
+ALTER TABLE sessions
+    ADD COLUMN IF NOT EXISTS revoked_reason TEXT NULL;
@@ -0,0 +1,23 @@
+-- External identities (OAuth / OIDC), several providers per user.
+-- Replaces users.oauth_provider / users.oauth_subject, which are kept for rollback but no longer written.
+CREATE TABLE IF NOT EXISTS user_identities (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    provider TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    -- Email reported by the provider at the last sign-in, and whether it said it was verified.
+    email TEXT NULL,
+    email_verified BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_login_at TIMESTAMPTZ NULL,
+    CONSTRAINT user_identities_provider_subject_unique UNIQUE (provider, subject),
+    CONSTRAINT user_identities_user_provider_unique UNIQUE (user_id, provider)
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_identities_user ON user_identities (user_id);
+
+INSERT INTO user_identities (user_id, provider, subject, email, email_verified, created_at)
+SELECT id, oauth_provider, oauth_subject, email, email_verified_at IS NOT NULL, created_at
+FROM users
+WHERE oauth_provider IS NOT NULL AND oauth_subject IS NOT NULL
+ON CONFLICT DO NOTHING;
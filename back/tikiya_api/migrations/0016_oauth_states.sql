@@ -0,0 +1,16 @@
+-- Pending OAuth authorizations: makes `state` single-use and keeps the PKCE verifier server-side
+CREATE TABLE IF NOT EXISTS oauth_states (
+    -- nonce carried in the signed state parameter
+    nonce TEXT PRIMARY KEY,
+    provider TEXT NOT NULL,
+    code_verifier TEXT NOT NULL,
+    -- OIDC `nonce` sent to the provider and expected back in the ID token
+    oidc_nonce TEXT NOT NULL,
+    -- opaque value from the caller, returned by the callback
+    client_state TEXT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_oauth_states_expires ON oauth_states (expires_at);
@@ -0,0 +1,12 @@
+-- Passwordless sign-in: single-use, expiring magic links stored HMAC-hashed. Keyed by email, not
+-- user, because the account is created when the first link is used.
+CREATE TABLE IF NOT EXISTS magic_link_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    email TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_magic_link_tokens_email ON magic_link_tokens (lower(email), created_at);
@@ -0,0 +1,28 @@
+-- Phone sign-in: E.164 numbers on users (verified by SMS code), email optional for phone-only
+-- accounts, and single-use one-time codes stored HMAC-hashed.
+ALTER TABLE users
+    ALTER COLUMN email DROP NOT NULL,
+    ADD COLUMN IF NOT EXISTS phone_verified_at TIMESTAMPTZ NULL;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'users_email_or_phone') THEN
+        ALTER TABLE users ADD CONSTRAINT users_email_or_phone CHECK (email IS NOT NULL OR phone_verified_at IS NOT NULL);
+    END IF;
+END $$;
+
+-- A number signs in to at most one account once verified; unverified profile numbers may repeat.
+CREATE UNIQUE INDEX IF NOT EXISTS users_phone_verified_key ON users (phone) WHERE phone_verified_at IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS phone_otps (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- E.164
+    phone TEXT NOT NULL,
+    code_hash TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_phone_otps_phone ON phone_otps (phone, created_at);
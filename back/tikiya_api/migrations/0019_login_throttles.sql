@@ -0,0 +1,15 @@
+-- Login backoff counters, replacing the account-wide 5-strike lockout. One row per
+-- (scope, key): scope 'ip_account' is keyed "<ip>|<lowercased email>", scope 'ip' by IP alone.
+CREATE TABLE IF NOT EXISTS login_throttles (
+    scope TEXT NOT NULL CHECK (scope IN ('ip_account', 'ip')),
+    key TEXT NOT NULL,
+    failures INTEGER NOT NULL DEFAULT 0,
+    last_failure_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    blocked_until TIMESTAMPTZ NULL,
+    PRIMARY KEY (scope, key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_login_throttles_last_failure ON login_throttles (last_failure_at);
+
+-- users.failed_attempts / lockout_until are no longer used by login; release current lockouts.
+UPDATE users SET failed_attempts = 0, lockout_until = NULL WHERE lockout_until IS NOT NULL OR failed_attempts <> 0;
@@ -0,0 +1,45 @@
+-- Organizer onboarding: applications reviewed by admins; approval creates the organization
+-- that events belong to and grants the applicant the organizer role.
+CREATE TABLE IF NOT EXISTS organizations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL,
+    -- registre du commerce / NIF
+    legal_id TEXT NOT NULL UNIQUE,
+    contact_email TEXT NOT NULL,
+    contact_phone TEXT NOT NULL,
+    owner_user_id UUID NOT NULL REFERENCES users(id) ON DELETE RESTRICT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_organizations_owner ON organizations (owner_user_id);
+
+CREATE TABLE IF NOT EXISTS organizer_applications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    organization_name TEXT NOT NULL,
+    legal_id TEXT NOT NULL,
+    contact_name TEXT NOT NULL,
+    contact_email TEXT NOT NULL,
+    contact_phone TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected')),
+    review_note TEXT NULL,
+    reviewed_by UUID NULL REFERENCES users(id) ON DELETE SET NULL,
+    reviewed_at TIMESTAMPTZ NULL,
+    organization_id UUID NULL REFERENCES organizations(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One open application per user.
+CREATE UNIQUE INDEX IF NOT EXISTS organizer_applications_one_pending ON organizer_applications (user_id) WHERE status = 'pending';
+CREATE INDEX IF NOT EXISTS idx_organizer_applications_status ON organizer_applications (status, created_at);
+
+-- Supporting documents (registre du commerce, pièce d'identité...), uploaded elsewhere and referenced by URL.
+CREATE TABLE IF NOT EXISTS organizer_application_documents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    application_id UUID NOT NULL REFERENCES organizer_applications(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    url TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_organizer_application_documents_app ON organizer_application_documents (application_id);
@@ -0,0 +1,28 @@
+-- Admin user management: account status (suspend / ban) and the trail of admin actions
+DO $$
+BEGIN
+    CREATE TYPE account_status AS ENUM ('active', 'suspended', 'banned');
+EXCEPTION
+    WHEN duplicate_object THEN NULL;
+END $$;
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS status account_status NOT NULL DEFAULT 'active',
+    ADD COLUMN IF NOT EXISTS status_reason TEXT NULL;
+
+-- Admin listing pages newest first.
+CREATE INDEX IF NOT EXISTS idx_users_created ON users (created_at DESC, id DESC);
+
+CREATE TABLE IF NOT EXISTS admin_audit_log (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    admin_id UUID NULL REFERENCES users(id) ON DELETE SET NULL,
+    -- e.g. 'user.role_changed', 'user.banned'
+    action TEXT NOT NULL,
+    target_user_id UUID NULL REFERENCES users(id) ON DELETE SET NULL,
+    details JSONB NOT NULL DEFAULT '{}'::jsonb,
+    ip TEXT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_admin_audit_log_target ON admin_audit_log (target_user_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_admin_audit_log_created ON admin_audit_log (created_at);
@@ -0,0 +1,7 @@
+-- Account status enforced at sign-in: expiring suspensions/bans and scheduled deletion
+ALTER TYPE account_status ADD VALUE IF NOT EXISTS 'pending_deletion';
+
+ALTER TABLE users
+    -- end of a suspension or ban (NULL = indefinite); for pending_deletion, when the account is deleted
+    ADD COLUMN IF NOT EXISTS status_until TIMESTAMPTZ NULL,
+    ADD COLUMN IF NOT EXISTS status_changed_at TIMESTAMPTZ NULL;
@@ -0,0 +1,11 @@
+-- Self-service deletion: once the grace period is over, accounts are erased, or anonymized when
+-- records that must be kept (organizations, later orders) point to them.
+ALTER TYPE account_status ADD VALUE IF NOT EXISTS 'deleted';
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ NULL;
+
+-- Anonymized rows keep neither email nor phone.
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_email_or_phone;
+ALTER TABLE users ADD CONSTRAINT users_email_or_phone CHECK (email IS NOT NULL OR phone_verified_at IS NOT NULL OR deleted_at IS NOT NULL);
+
+CREATE INDEX IF NOT EXISTS idx_users_deletion_due ON users (status_until) WHERE status = 'pending_deletion';
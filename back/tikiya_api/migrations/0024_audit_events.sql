@@ -0,0 +1,33 @@
+-- Persistent security audit trail (sign-ins, refreshes, logouts, identity links, admin actions),
+-- replacing admin_audit_log.
+CREATE TABLE IF NOT EXISTS audit_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- dotted name, e.g. 'auth.login', 'admin.user.role_changed'
+    event_type TEXT NOT NULL,
+    outcome TEXT NOT NULL CHECK (outcome IN ('success', 'failure')),
+    -- who acted (the user, or an admin); NULL for anonymous attempts
+    actor_user_id UUID NULL REFERENCES users(id) ON DELETE SET NULL,
+    -- whose account the event concerns
+    subject_user_id UUID NULL REFERENCES users(id) ON DELETE SET NULL,
+    ip TEXT NULL,
+    user_agent TEXT NULL,
+    request_id TEXT NULL,
+    details JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_events_subject ON audit_events (subject_user_id, created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_events_actor ON audit_events (actor_user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_events_type ON audit_events (event_type, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_events_created ON audit_events (created_at DESC, id DESC);
+
+DO $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM information_schema.tables WHERE table_schema = 'public' AND table_name = 'admin_audit_log') THEN
+        INSERT INTO audit_events (id, event_type, outcome, actor_user_id, subject_user_id, ip, details, created_at)
+        SELECT id, 'admin.' || action, 'success', admin_id, target_user_id, ip, details, created_at
+        FROM admin_audit_log
+        ON CONFLICT (id) DO NOTHING;
+        DROP TABLE admin_audit_log;
+    END IF;
+END $$;
@@ -0,0 +1,3 @@
+-- TOTP secrets are encrypted at rest (AES-256-GCM keyed from MFA_ENCRYPTION_KEY). The API seals
+-- rows still in plaintext when it starts.
+COMMENT ON COLUMN user_mfa_totp.secret IS 'v1:<base64url(nonce || ciphertext)>, AES-256-GCM with the user id as associated data';
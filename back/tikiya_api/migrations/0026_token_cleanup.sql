@@ -0,0 +1,10 @@
+-- Expired one-time rows are now purged hourly; index the expiry of the tables that lacked one.
+-- oauth_states records the caller's IP to cap pending authorizations per client.
+ALTER TABLE oauth_states ADD COLUMN IF NOT EXISTS ip TEXT NULL;
+CREATE INDEX IF NOT EXISTS idx_oauth_states_ip_pending ON oauth_states (ip, expires_at) WHERE consumed_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_email_verification_tokens_expires ON email_verification_tokens (expires_at);
+CREATE INDEX IF NOT EXISTS idx_password_reset_tokens_expires ON password_reset_tokens (expires_at);
+CREATE INDEX IF NOT EXISTS idx_mfa_challenges_expires ON mfa_challenges (expires_at);
+CREATE INDEX IF NOT EXISTS idx_magic_link_tokens_expires ON magic_link_tokens (expires_at);
+CREATE INDEX IF NOT EXISTS idx_phone_otps_expires ON phone_otps (expires_at);
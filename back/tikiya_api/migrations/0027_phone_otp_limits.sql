@@ -0,0 +1,4 @@
+-- Caller IP of each phone code request, for the per-IP and daily per-number send limits.
+ALTER TABLE phone_otps ADD COLUMN IF NOT EXISTS ip TEXT NULL;
+CREATE INDEX IF NOT EXISTS idx_phone_otps_ip ON phone_otps (ip, created_at);
+CREATE INDEX IF NOT EXISTS idx_phone_otps_created ON phone_otps (created_at);
@@ -0,0 +1,21 @@
+-- Signing in during the deletion grace period offers a cancel: single-use tokens handed out
+-- after a successful sign-in and exchanged at POST /auth/deletion/cancel.
+CREATE TABLE IF NOT EXISTS deletion_cancel_tokens (
+    token_hash TEXT PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- the sign-in that earned the token proved two factors; carried over to the new session
+    second_factor BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_deletion_cancel_tokens_user ON deletion_cancel_tokens (user_id);
+CREATE INDEX IF NOT EXISTS idx_deletion_cancel_tokens_expires ON deletion_cancel_tokens (expires_at);
+
+-- Approved applications of an erased owner stay with the organization, without the contact
+-- person's details.
+ALTER TABLE organizer_applications
+    ALTER COLUMN contact_name DROP NOT NULL,
+    ALTER COLUMN contact_email DROP NOT NULL,
+    ALTER COLUMN contact_phone DROP NOT NULL;
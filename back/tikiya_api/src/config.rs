@@ -1,5 +1,65 @@
 use std::env;
 
+use crate::models::Role;
+
+/// One OAuth 2.0 / OpenID Connect identity provider (`OIDC_<NAME>_*`).
+#[derive(Clone, Debug)]
+pub struct OidcProviderConfig {
+    /// Lowercase name used in routes (`/auth/{name}`) and in `user_identities.provider`.
+    pub name: String,
+    /// Expected `iss`; the discovery document is read from `{issuer}/.well-known/openid-configuration`.
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_uri: String,
+    pub scopes: String,
+    /// Extra `aud` values accepted for ID tokens (e.g. the iOS / Android client ids).
+    pub extra_audiences: Vec<String>,
+    /// Set for providers that post the callback (`form_post`, required by Apple for email scope).
+    pub response_mode: Option<String>,
+    pub discovery_url: Option<String>,
+    // Endpoint overrides, for providers whose discovery document is incomplete (Facebook).
+    pub authorization_endpoint: Option<String>,
+    pub token_endpoint: Option<String>,
+    pub userinfo_endpoint: Option<String>,
+    pub jwks_uri: Option<String>,
+}
+
+/// Argon2id cost parameters for new password hashes (`ARGON2_*`) and optional `PASSWORD_PEPPER`.
+/// Hashes computed with other values are upgraded on the next successful login.
+#[derive(Clone, Debug)]
+pub struct PasswordHashConfig {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    pub pepper: Option<String>,
+}
+
+/// Rules for new passwords (`PASSWORD_MIN_LENGTH`, `PASSWORD_MIN_SCORE`, `PASSWORD_BREACHED_LIST`).
+#[derive(Clone, Debug)]
+pub struct PasswordPolicyConfig {
+    pub min_length: usize,
+    /// Minimum strength, 0 (anything) to 4.
+    pub min_score: u8,
+    /// File of SHA-1 hashes of breached passwords, one per line (`HASH` or `HASH:count`), sorted
+    /// by hash; searched on disk.
+    pub breached_list_path: Option<String>,
+}
+
+/// Password login backoff (`LOGIN_*`). Failures are counted per (IP, email) and per IP across
+/// emails; past the free attempts each failure doubles the wait, up to `max_delay_secs`.
+#[derive(Clone, Debug)]
+pub struct LoginThrottleConfig {
+    pub free_attempts: i32,
+    pub ip_free_attempts: i32,
+    pub base_delay_secs: i64,
+    pub max_delay_secs: i64,
+    /// A counter idle for this long starts over.
+    pub failure_window_secs: i64,
+    /// Failures after which error bodies carry `captcha_required: true` (0 = never).
+    pub captcha_after: i32,
+}
+
 #[derive(Clone, Debug)]
 pub struct AppConfig {
     pub port: u16,
@@ -16,9 +76,41 @@ pub struct AppConfig {
     pub jwt_secret: String,
     pub jwt_issuer: String,
     pub jwt_audience: String,
-    pub google_client_id: String,
-    pub google_client_secret: String,
-    pub google_redirect_uri: String,
+    pub jwt_signing_key_file: Option<String>,
+    pub jwt_verify_key_files: Vec<String>,
+    pub jwt_accept_hs256: bool,
+    pub refresh_token_secret: String,
+    /// `JWT_SECRET` when `REFRESH_TOKEN_SECRET` is unset: refresh hashes stored before the key was
+    /// derived still verify (with `refresh_accept_legacy_hashes`) until their next rotation.
+    pub refresh_token_legacy_secret: Option<String>,
+    /// Accept refresh tokens whose session still stores a pre-HMAC Argon2 hash.
+    pub refresh_accept_legacy_hashes: bool,
+    pub password_hash: PasswordHashConfig,
+    pub password_policy: PasswordPolicyConfig,
+    pub oauth_state_secret: String,
+    pub one_time_token_secret: String,
+    /// `JWT_SECRET` when `ONE_TIME_TOKEN_SECRET` is unset, for recovery codes hashed with it.
+    pub one_time_token_legacy_secret: Option<String>,
+    /// Key material for the TOTP secrets encrypted at rest.
+    pub mfa_encryption_key: String,
+    pub mfa_required_roles: Vec<Role>,
+    pub mfa_issuer: String,
+    pub login_throttle: LoginThrottleConfig,
+    pub oidc_providers: Vec<OidcProviderConfig>,
+    pub app_public_url: String,
+    pub mail_transport: String,
+    pub mail_from: String,
+    pub mail_outbox_dir: String,
+    pub sms_transport: String,
+    pub sms_outbox_dir: String,
+    /// Codes texted per hour across all numbers; beyond it requests are dropped.
+    pub sms_hourly_limit: i64,
+    pub phone_default_country_code: String,
+    /// Days between `DELETE /me` and the erasure of the account.
+    pub account_deletion_grace_days: i64,
+    pub webauthn_rp_id: String,
+    pub webauthn_rp_name: String,
+    pub webauthn_origins: Vec<String>,
 }
 
 impl AppConfig {
@@ -101,10 +193,151 @@ impl AppConfig {
         }
         let jwt_issuer = env::var("JWT_ISSUER").unwrap_or_else(|_| "tikiya-api".to_string());
         let jwt_audience = env::var("JWT_AUDIENCE").unwrap_or_else(|_| "tikiya-clients".to_string());
-        // Google OAuth config is optional; only required if you use Google endpoints
-        let google_client_id = env::var("GOOGLE_CLIENT_ID").unwrap_or_default();
-        let google_client_secret = env::var("GOOGLE_CLIENT_SECRET").unwrap_or_default();
-        let google_redirect_uri = env::var("GOOGLE_REDIRECT_URI").unwrap_or_default();
+
+        // Asymmetric access-token signing (EdDSA / RS256). Without it tokens stay HS256 on JWT_SECRET.
+        let jwt_signing_key_file = env::var("JWT_SIGNING_KEY_FILE")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+        let jwt_verify_key_files = env::var("JWT_VERIFY_KEY_FILES")
+            .map(|v| {
+                v.split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        // Keep accepting HS256 tokens during the switch to asymmetric keys (they live 15 minutes).
+        let jwt_accept_hs256 = env::var("JWT_ACCEPT_HS256")
+            .ok()
+            .map(|v| {
+                let v = v.to_lowercase();
+                v == "1" || v == "true" || v == "yes"
+            })
+            .unwrap_or(jwt_signing_key_file.is_none());
+
+        // One secret per purpose. When unset, a key is derived from JWT_SECRET for that purpose alone.
+        let (refresh_token_secret, refresh_derived) =
+            secret_or_derived("REFRESH_TOKEN_SECRET", &jwt_secret, "tikiya/refresh-token/v1");
+        let refresh_token_legacy_secret = refresh_derived.then(|| jwt_secret.clone());
+        // Sessions created before HMAC refresh hashes switch to HMAC on their next refresh. Turn
+        // this off once none are left (their refresh tokens are then rejected).
+        let refresh_accept_legacy_hashes = env::var("REFRESH_ACCEPT_LEGACY_HASHES")
+            .ok()
+            .map(|v| {
+                let v = v.to_lowercase();
+                v == "1" || v == "true" || v == "yes"
+            })
+            .unwrap_or(true);
+        let (oauth_state_secret, _) = secret_or_derived("OAUTH_STATE_SECRET", &jwt_secret, "tikiya/oauth-state/v1");
+
+        // Defaults are the argon2 crate's (OWASP minimum: 19 MiB, 2 passes, 1 lane).
+        let argon2_param = |name: &str, default: u32| -> u32 {
+            env::var(name).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(default)
+        };
+        let password_hash = PasswordHashConfig {
+            memory_kib: argon2_param("ARGON2_MEMORY_KIB", argon2::Params::DEFAULT_M_COST),
+            iterations: argon2_param("ARGON2_ITERATIONS", argon2::Params::DEFAULT_T_COST),
+            parallelism: argon2_param("ARGON2_PARALLELISM", argon2::Params::DEFAULT_P_COST),
+            pepper: env::var("PASSWORD_PEPPER")
+                .ok()
+                .filter(|v| !v.trim().is_empty()),
+        };
+        let password_policy = PasswordPolicyConfig {
+            min_length: env::var("PASSWORD_MIN_LENGTH").ok().and_then(|v| v.trim().parse().ok()).unwrap_or(8),
+            min_score: env::var("PASSWORD_MIN_SCORE")
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(2u8)
+                .min(4),
+            breached_list_path: env::var("PASSWORD_BREACHED_LIST")
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty()),
+        };
+        if let Err(e) = argon2::Params::new(password_hash.memory_kib, password_hash.iterations, password_hash.parallelism, None) {
+            panic!("ARGON2_* invalides: {}", e);
+        }
+        let (one_time_token_secret, one_time_derived) =
+            secret_or_derived("ONE_TIME_TOKEN_SECRET", &jwt_secret, "tikiya/one-time-token/v1");
+        let one_time_token_legacy_secret = one_time_derived.then(|| jwt_secret.clone());
+        // Roles that must have completed MFA to use role-guarded routes, e.g. "admin,organizer".
+        let mfa_required_roles = env::var("MFA_REQUIRED_ROLES")
+            .map(|v| {
+                v.split(',')
+                    .map(|s| s.trim())
+                    .filter(|s| !s.is_empty())
+                    .map(|s| {
+                        s.parse::<Role>()
+                            .unwrap_or_else(|e| panic!("MFA_REQUIRED_ROLES: {}", e))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        // Encrypts TOTP secrets in the database. Changing it makes enrolled authenticators unusable.
+        let (mfa_encryption_key, _) = secret_or_derived("MFA_ENCRYPTION_KEY", &jwt_secret, "tikiya/mfa-encryption/v1");
+        let mfa_issuer = env::var("MFA_ISSUER").unwrap_or_else(|_| "Tikiya".to_string());
+
+        let env_num = |name: &str, default: i64| -> i64 {
+            env::var(name).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(default)
+        };
+        let login_throttle = LoginThrottleConfig {
+            free_attempts: env_num("LOGIN_FREE_ATTEMPTS", 3) as i32,
+            ip_free_attempts: env_num("LOGIN_IP_FREE_ATTEMPTS", 20) as i32,
+            base_delay_secs: env_num("LOGIN_BACKOFF_BASE_SECS", 2).max(1),
+            max_delay_secs: env_num("LOGIN_BACKOFF_MAX_SECS", 15 * 60).max(1),
+            failure_window_secs: env_num("LOGIN_FAILURE_WINDOW_SECS", 60 * 60).max(60),
+            captcha_after: env_num("LOGIN_CAPTCHA_AFTER", 0) as i32,
+        };
+
+        // OAuth / OpenID Connect providers are optional; only required if you use /auth/{provider}
+        let oidc_providers = oidc_providers_from_env();
+
+        // Base URL of the web front, used to build links sent by email.
+        let app_public_url = env::var("APP_PUBLIC_URL")
+            .map(|v| v.trim_end_matches('/').to_string())
+            .unwrap_or_else(|_| "http://localhost:3000".to_string());
+        let mail_transport = env::var("MAIL_TRANSPORT")
+            .map(|v| v.trim().to_lowercase())
+            .unwrap_or_else(|_| "log".to_string());
+        let mail_from = env::var("MAIL_FROM").unwrap_or_else(|_| "Tikiya <no-reply@tikiya.app>".to_string());
+        let mail_outbox_dir = env::var("MAIL_OUTBOX_DIR").unwrap_or_else(|_| "./outbox".to_string());
+        let sms_transport = env::var("SMS_TRANSPORT")
+            .map(|v| v.trim().to_lowercase())
+            .unwrap_or_else(|_| "log".to_string());
+        let sms_outbox_dir = env::var("SMS_OUTBOX_DIR").unwrap_or_else(|_| "./outbox/sms".to_string());
+        let sms_hourly_limit = env_num("SMS_HOURLY_LIMIT", 500).max(1);
+        // Calling code assumed for national numbers ("0555 12 34 56"), digits only.
+        let phone_default_country_code = env::var("PHONE_DEFAULT_COUNTRY_CODE")
+            .map(|v| v.trim().trim_start_matches('+').to_string())
+            .ok()
+            .filter(|v| !v.is_empty() && v.chars().all(|c| c.is_ascii_digit()))
+            .unwrap_or_else(|| "213".to_string());
+        let account_deletion_grace_days = env::var("ACCOUNT_DELETION_GRACE_DAYS")
+            .ok()
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .filter(|v| *v >= 0)
+            .unwrap_or(30);
+
+        // WebAuthn relying party. The RP id is a registrable domain (no scheme/port); origins are
+        // the exact origins allowed in client data (web URL, `android:apk-key-hash:...`, etc.).
+        let webauthn_rp_id = env::var("WEBAUTHN_RP_ID")
+            .map(|v| v.trim().to_string())
+            .ok()
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| {
+                let host = app_public_url.split("://").nth(1).unwrap_or(&app_public_url);
+                host.split(['/', ':']).next().unwrap_or_default().to_string()
+            });
+        let webauthn_rp_name = env::var("WEBAUTHN_RP_NAME").unwrap_or_else(|_| "Tikiya".to_string());
+        let webauthn_origins = env::var("WEBAUTHN_ORIGINS")
+            .map(|v| {
+                v.split(',')
+                    .map(|s| s.trim().trim_end_matches('/').to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_else(|_| vec![app_public_url.clone()]);
 
         Self {
             port,
@@ -121,13 +354,114 @@ impl AppConfig {
             jwt_secret,
             jwt_issuer,
             jwt_audience,
-            google_client_id,
-            google_client_secret,
-            google_redirect_uri,
+            jwt_signing_key_file,
+            jwt_verify_key_files,
+            jwt_accept_hs256,
+            refresh_token_secret,
+            refresh_token_legacy_secret,
+            refresh_accept_legacy_hashes,
+            password_hash,
+            password_policy,
+            oauth_state_secret,
+            one_time_token_secret,
+            one_time_token_legacy_secret,
+            mfa_encryption_key,
+            mfa_required_roles,
+            mfa_issuer,
+            login_throttle,
+            oidc_providers,
+            app_public_url,
+            mail_transport,
+            mail_from,
+            mail_outbox_dir,
+            sms_transport,
+            sms_outbox_dir,
+            sms_hourly_limit,
+            phone_default_country_code,
+            account_deletion_grace_days,
+            webauthn_rp_id,
+            webauthn_rp_name,
+            webauthn_origins,
         }
     }
 }
 
+/// Providers listed in `OIDC_PROVIDERS` (e.g. "google,microsoft,apple,facebook,keycloak"), each
+/// read from `OIDC_<NAME>_*`. Well-known names get default issuers and overrides. The legacy
+/// `GOOGLE_*` variables still configure the "google" provider.
+fn oidc_providers_from_env() -> Vec<OidcProviderConfig> {
+    let mut names: Vec<String> = env::var("OIDC_PROVIDERS")
+        .map(|v| {
+            v.split(',')
+                .map(|s| s.trim().to_lowercase())
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    let legacy_google = env::var("GOOGLE_CLIENT_ID").is_ok_and(|v| !v.trim().is_empty());
+    if legacy_google && !names.iter().any(|n| n == "google") {
+        names.push("google".to_string());
+    }
+
+    names
+        .into_iter()
+        .map(|name| {
+            if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+                panic!("OIDC_PROVIDERS: nom de provider invalide '{}'", name);
+            }
+            let prefix = format!("OIDC_{}_", name.to_uppercase().replace('-', "_"));
+            let var = |key: &str| {
+                env::var(format!("{prefix}{key}"))
+                    .ok()
+                    .map(|v| v.trim().to_string())
+                    .filter(|v| !v.is_empty())
+            };
+            let google_var = |key: &str| {
+                (name == "google")
+                    .then(|| env::var(format!("GOOGLE_{key}")).ok())
+                    .flatten()
+                    .map(|v| v.trim().to_string())
+                    .filter(|v| !v.is_empty())
+            };
+
+            let (default_issuer, default_scopes) = match name.as_str() {
+                "google" => (Some("https://accounts.google.com"), "openid email profile"),
+                "microsoft" => (Some("https://login.microsoftonline.com/common/v2.0"), "openid email profile"),
+                "apple" => (Some("https://appleid.apple.com"), "openid email name"),
+                "facebook" => (Some("https://www.facebook.com"), "openid email public_profile"),
+                _ => (None, "openid email profile"),
+            };
+            let required = |key: &str, value: Option<String>| {
+                value.unwrap_or_else(|| panic!("Configuration manquante: {}{}", prefix, key))
+            };
+
+            OidcProviderConfig {
+                issuer: required("ISSUER", var("ISSUER").or(default_issuer.map(str::to_string))),
+                client_id: required("CLIENT_ID", var("CLIENT_ID").or_else(|| google_var("CLIENT_ID"))),
+                client_secret: var("CLIENT_SECRET").or_else(|| google_var("CLIENT_SECRET")).unwrap_or_default(),
+                redirect_uri: required("REDIRECT_URI", var("REDIRECT_URI").or_else(|| google_var("REDIRECT_URI"))),
+                scopes: var("SCOPES").unwrap_or_else(|| default_scopes.to_string()),
+                extra_audiences: var("EXTRA_AUDIENCES")
+                    .map(|v| v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+                    .unwrap_or_default(),
+                response_mode: var("RESPONSE_MODE").or_else(|| (name == "apple").then(|| "form_post".to_string())),
+                discovery_url: var("DISCOVERY_URL"),
+                authorization_endpoint: var("AUTHORIZATION_ENDPOINT"),
+                // Facebook's discovery document only covers its limited (ID token) login.
+                token_endpoint: var("TOKEN_ENDPOINT").or_else(|| {
+                    (name == "facebook").then(|| "https://graph.facebook.com/v19.0/oauth/access_token".to_string())
+                }),
+                userinfo_endpoint: var("USERINFO_ENDPOINT").or_else(|| {
+                    (name == "facebook").then(|| "https://graph.facebook.com/me?fields=id,email,first_name,last_name".to_string())
+                }),
+                // GOOGLE_JWKS_URL: overridable so tests can point ID-token verification at a local key server.
+                jwks_uri: var("JWKS_URI").or_else(|| google_var("JWKS_URL")),
+                name,
+            }
+        })
+        .collect()
+}
+
 fn must_env(key: &str) -> String {
     match env::var(key) {
         Ok(v) if !v.trim().is_empty() => v,
@@ -135,6 +469,35 @@ fn must_env(key: &str) -> String {
     }
 }
 
+/// The secret in `key`, or one derived from `JWT_SECRET` with HKDF-SHA256 under `label` (never
+/// `JWT_SECRET` itself, so a leaked key of one purpose does not open the others). The flag is
+/// true for a derived secret.
+fn secret_or_derived(key: &str, jwt_secret: &str, label: &str) -> (String, bool) {
+    match env::var(key) {
+        Ok(v) if !v.trim().is_empty() => {
+            let v = v.trim().to_string();
+            if v.len() < 32 || v == jwt_secret.trim() {
+                panic!("{} trop faible (min 32 chars et différent de JWT_SECRET)", key);
+            }
+            (v, false)
+        }
+        _ => {
+            tracing::warn!(key = %key, "config.secret_derived_from_jwt_secret");
+            (derive_secret(jwt_secret, label), true)
+        }
+    }
+}
+
+fn derive_secret(master: &str, label: &str) -> String {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+    let mut okm = [0u8; 32];
+    hkdf::Hkdf::<sha2::Sha256>::new(None, master.as_bytes())
+        .expand(label.as_bytes(), &mut okm)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    URL_SAFE_NO_PAD.encode(okm)
+}
+
 fn load_dotenv_if_exists() {
     use std::fs;
     if let Ok(content) = fs::read_to_string(".env") {
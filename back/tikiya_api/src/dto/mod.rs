@@ -1,16 +1,52 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use validator::Validate;
+use validator::{Validate, ValidationError};
 
-use crate::models::User;
+use crate::models::{AccountStatus, Role, User};
+
+/// Languages the mobile apps ship translations for.
+pub const SUPPORTED_LANGUAGES: &[&str] = &["fr", "ar", "en"];
+
+fn validate_language(value: &str) -> Result<(), ValidationError> {
+    if value.is_empty() || SUPPORTED_LANGUAGES.contains(&value) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("unsupported_language"))
+    }
+}
 
 #[derive(Debug, Deserialize, Validate)]
 pub struct RegisterRequest {
     #[validate(email)]
     pub email: String,
-    #[validate(length(min = 8, max = 128))]
+    #[validate(length(max = 128))]
     pub password: String,
+    #[validate(length(max = 100))]
+    pub first_name: Option<String>,
+    #[validate(length(max = 100))]
+    pub last_name: Option<String>,
+    #[validate(length(max = 32))]
+    pub phone: Option<String>,
+    #[validate(custom(function = "validate_language"))]
+    pub preferred_language: Option<String>,
+    #[validate(length(max = 100))]
+    pub city: Option<String>,
+}
+
+/// `PATCH /me` body: omitted fields are left untouched, empty strings clear the field.
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateProfileRequest {
+    #[validate(length(max = 100))]
+    pub first_name: Option<String>,
+    #[validate(length(max = 100))]
+    pub last_name: Option<String>,
+    #[validate(length(max = 32))]
+    pub phone: Option<String>,
+    #[validate(custom(function = "validate_language"))]
+    pub preferred_language: Option<String>,
+    #[validate(length(max = 100))]
+    pub city: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Validate, Clone)]
@@ -24,8 +60,15 @@ pub struct LoginRequest {
 #[derive(Debug, Serialize)]
 pub struct UserResponse {
     pub id: Uuid,
-    pub email: String,
-    pub role: String,
+    pub email: Option<String>,
+    pub role: Role,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub phone: Option<String>,
+    pub preferred_language: Option<String>,
+    pub city: Option<String>,
+    pub email_verified: bool,
+    pub phone_verified: bool,
     pub created_at: DateTime<Utc>,
 }
 
@@ -47,19 +90,445 @@ pub struct LogoutRequest {
     pub refresh_token: String,
 }
 
+#[derive(Debug, Deserialize, Validate)]
+pub struct VerifyEmailRequest {
+    #[validate(length(min = 1))]
+    pub token: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct MagicLinkRequest {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct MagicLinkVerifyRequest {
+    #[validate(length(min = 1))]
+    pub token: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct PhoneCodeRequest {
+    #[validate(length(min = 1, max = 32))]
+    pub phone: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct PhoneVerifyRequest {
+    #[validate(length(min = 1, max = 32))]
+    pub phone: String,
+    #[validate(length(equal = 6))]
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ForgotPasswordRequest {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ResetPasswordRequest {
+    #[validate(length(min = 1))]
+    pub token: String,
+    #[validate(length(max = 128))]
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ChangePasswordRequest {
+    /// `current_password` when the account already has one; setting a first password takes a
+    /// second factor or a recent sign-in instead.
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub reauth: ReauthRequest,
+    #[validate(length(max = 128))]
+    pub new_password: String,
+    #[serde(default)]
+    pub revoke_other_sessions: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionResponse {
+    pub id: Uuid,
+    pub device_label: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>,
+    /// True for the session the calling access token belongs to.
+    pub current: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevokeSessionsResponse {
+    pub revoked: u64,
+}
+
 #[derive(Debug, Serialize)]
 pub struct AuthResponse {
     pub user: UserResponse,
     pub tokens: AuthTokens,
 }
 
+/// First-factor login result: tokens, or an MFA challenge to complete at `POST /login/mfa`.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Authenticated(AuthResponse),
+    MfaRequired(MfaChallengeResponse),
+}
+
+/// OAuth callback result, with the `state` the caller passed to `/auth/{provider}` so it can
+/// restore its navigation.
+#[derive(Debug, Serialize)]
+pub struct OAuthCallbackResponse {
+    #[serde(flatten)]
+    pub login: LoginResponse,
+    pub client_state: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MfaChallengeResponse {
+    pub mfa_required: bool,
+    pub challenge_token: String,
+    pub expires_in: i64,
+}
+
+/// A TOTP `code` or one of the single-use `recovery_code`s.
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct MfaCodeRequest {
+    #[validate(length(min = 6, max = 10))]
+    pub code: Option<String>,
+    #[validate(length(min = 1, max = 32))]
+    pub recovery_code: Option<String>,
+}
+
+/// Re-authentication before a change that outlives the session: the `current_password` of
+/// accounts that have one; otherwise a TOTP `code` / `recovery_code`, or nothing when the
+/// session was signed in within the last few minutes.
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct ReauthRequest {
+    #[validate(length(max = 128))]
+    pub current_password: Option<String>,
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub mfa: MfaCodeRequest,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ConfirmTotpRequest {
+    #[validate(length(min = 6, max = 10))]
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct MfaLoginRequest {
+    #[validate(length(min = 1))]
+    pub challenge_token: String,
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub proof: MfaCodeRequest,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TotpEnrollmentResponse {
+    /// Base32 secret, for manual entry in the authenticator app.
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
 impl From<&User> for UserResponse {
     fn from(user: &User) -> Self {
         Self {
             id: user.id,
             email: user.email.clone(),
-            role: user.role.clone(),
+            role: user.role,
+            first_name: user.first_name.clone(),
+            last_name: user.last_name.clone(),
+            phone: user.phone.clone(),
+            preferred_language: user.preferred_language.clone(),
+            city: user.city.clone(),
+            email_verified: user.email_verified_at.is_some(),
+            phone_verified: user.phone_verified_at.is_some(),
             created_at: user.created_at,
         }
     }
 }
+
+/// Options for `navigator.credentials.create()` / `.get()`; `public_key` follows the WebAuthn
+/// JSON shape (camelCase, base64url binary fields).
+#[derive(Debug, Serialize)]
+pub struct WebauthnOptionsResponse {
+    pub public_key: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct WebauthnRegisterFinishRequest {
+    #[validate(length(max = 100))]
+    pub name: Option<String>,
+    pub credential: WebauthnAttestationCredential,
+}
+
+/// `PublicKeyCredential.toJSON()` of a registration, binary fields base64url-encoded.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebauthnAttestationCredential {
+    pub id: String,
+    pub response: WebauthnAttestationResponse,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebauthnAttestationResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    pub attestation_object: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct WebauthnLoginStartRequest {
+    /// Restricts the ceremony to this account's passkeys; omit for discoverable credentials.
+    #[validate(email)]
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct WebauthnLoginFinishRequest {
+    pub credential: WebauthnAssertionCredential,
+}
+
+/// `PublicKeyCredential.toJSON()` of an assertion, binary fields base64url-encoded.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebauthnAssertionCredential {
+    pub id: String,
+    pub response: WebauthnAssertionResponse,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebauthnAssertionResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    pub authenticator_data: String,
+    pub signature: String,
+    pub user_handle: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebauthnCredentialResponse {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct LinkIdentityRequest {
+    #[validate(length(min = 1))]
+    pub id_token: String,
+    pub nonce: Option<String>,
+    /// A linked provider is a lasting way in: `current_password`, else a second factor or a
+    /// recent sign-in.
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub reauth: ReauthRequest,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IdentityResponse {
+    pub provider: String,
+    pub email: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_login_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct OrganizerApplicationRequest {
+    #[validate(length(min = 2, max = 200))]
+    pub organization_name: String,
+    /// Registre du commerce or NIF number.
+    #[validate(length(min = 2, max = 64))]
+    pub legal_id: String,
+    #[validate(length(min = 1, max = 200))]
+    pub contact_name: String,
+    #[validate(email)]
+    pub contact_email: String,
+    #[validate(length(min = 1, max = 32))]
+    pub contact_phone: String,
+    #[serde(default)]
+    #[validate(length(max = 10), nested)]
+    pub documents: Vec<SupportingDocument>,
+}
+
+/// A supporting document, uploaded to storage beforehand and referenced by URL.
+#[derive(Debug, Clone, Deserialize, Serialize, Validate)]
+pub struct SupportingDocument {
+    #[validate(length(min = 1, max = 200))]
+    pub name: String,
+    #[validate(url, length(max = 2048))]
+    pub url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrganizerApplicationResponse {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub organization_name: String,
+    pub legal_id: String,
+    /// Cleared once the applicant's account is erased.
+    pub contact_name: Option<String>,
+    pub contact_email: Option<String>,
+    pub contact_phone: Option<String>,
+    pub documents: Vec<SupportingDocument>,
+    /// `pending`, `approved` or `rejected`.
+    pub status: String,
+    pub review_note: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub organization_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ApproveApplicationRequest {
+    #[validate(length(max = 1000))]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RejectApplicationRequest {
+    #[validate(length(min = 1, max = 1000))]
+    pub reason: String,
+}
+
+/// A user as seen by admins: the profile plus account status.
+#[derive(Debug, Serialize)]
+pub struct AdminUserResponse {
+    #[serde(flatten)]
+    pub user: UserResponse,
+    pub status: AccountStatus,
+    pub status_reason: Option<String>,
+    pub status_until: Option<DateTime<Utc>>,
+}
+
+impl From<&User> for AdminUserResponse {
+    fn from(user: &User) -> Self {
+        Self {
+            user: UserResponse::from(user),
+            status: user.status,
+            status_reason: user.status_reason.clone(),
+            status_until: user.status_until,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminUserPage {
+    pub users: Vec<AdminUserResponse>,
+    /// Pass as `cursor` to fetch the next page; `None` on the last page.
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangeRoleRequest {
+    pub role: Role,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct AccountStatusRequest {
+    /// Shown to the user when sign-in is refused.
+    #[validate(length(min = 1, max = 1000))]
+    pub reason: String,
+    /// End of the suspension or ban; indefinite when omitted.
+    pub until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct DeleteAccountRequest {
+    /// `current_password` when the account has one; otherwise a second factor or a recent sign-in.
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub reauth: ReauthRequest,
+}
+
+/// `POST /auth/deletion/cancel`: the `cancel_token` from a sign-in refused with
+/// `Account Pending Deletion`.
+#[derive(Debug, Deserialize, Validate)]
+pub struct CancelDeletionRequest {
+    #[validate(length(min = 1, max = 128))]
+    pub cancel_token: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountDeletionResponse {
+    pub status: AccountStatus,
+    /// When the account's data is erased.
+    pub deletion_scheduled_at: DateTime<Utc>,
+}
+
+/// `GET /me/export`: everything the API stores about the caller.
+#[derive(Debug, Serialize)]
+pub struct AccountExport {
+    pub exported_at: DateTime<Utc>,
+    pub profile: UserResponse,
+    pub has_password: bool,
+    pub totp_enabled: bool,
+    pub sessions: Vec<ExportedSession>,
+    pub identities: Vec<IdentityResponse>,
+    pub passkeys: Vec<WebauthnCredentialResponse>,
+    pub organizer_applications: Vec<OrganizerApplicationResponse>,
+    pub organizations: Vec<ExportedOrganization>,
+}
+
+/// A session, revoked ones included.
+#[derive(Debug, Serialize)]
+pub struct ExportedSession {
+    pub id: Uuid,
+    pub device_label: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportedOrganization {
+    pub id: Uuid,
+    pub name: String,
+    pub legal_id: String,
+    pub contact_email: String,
+    pub contact_phone: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditEventResponse {
+    pub id: Uuid,
+    pub event_type: String,
+    /// `success` or `failure`.
+    pub outcome: String,
+    pub actor_user_id: Option<Uuid>,
+    pub subject_user_id: Option<Uuid>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub request_id: Option<String>,
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditEventPage {
+    pub events: Vec<AuditEventResponse>,
+    /// Pass as `cursor` to fetch the next page; `None` on the last page.
+    pub next_cursor: Option<String>,
+}
@@ -1,17 +1,47 @@
 use axum::{
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
+use chrono::{DateTime, Utc};
 use serde::Serialize;
 use thiserror::Error;
 
+use crate::models::AccountStatus;
+
 #[derive(Debug, Error)]
 pub enum ApiError {
     #[error("invalid data: {0}")]
     Validation(String),
+    /// Validation failures tied to request fields, so clients can show them next to the input.
+    #[error("invalid data: {}", format_field_errors(.0))]
+    InvalidFields(Vec<FieldError>),
     #[error("identifiants invalides")]
     Unauthorized,
+    /// Failed login after which the client should show a CAPTCHA.
+    #[error("identifiants invalides")]
+    CaptchaRequired,
+    #[error("too many attempts")]
+    TooManyAttempts { retry_after_secs: i64, captcha_required: bool },
+    #[error("forbidden")]
+    Forbidden,
+    #[error("email not verified")]
+    EmailNotVerified,
+    #[error("mfa required")]
+    MfaRequired,
+    /// The action needs the password, a second factor or a fresh sign-in.
+    #[error("reauthentication required")]
+    ReauthenticationRequired,
+    /// The account is suspended, banned or scheduled for deletion.
+    #[error("account {status}")]
+    AccountDisabled {
+        status: AccountStatus,
+        reason: Option<String>,
+        until: Option<DateTime<Utc>>,
+        /// Set when a sign-in succeeded on an account pending deletion: exchanging it at
+        /// `POST /auth/deletion/cancel` cancels the deletion.
+        cancel_token: Option<String>,
+    },
     #[error("resource not found")]
     NotFound,
     #[error("conflict: {0}")]
@@ -22,11 +52,54 @@ pub enum ApiError {
     Internal,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    /// Request body field, as named in the JSON.
+    pub field: String,
+    /// Stable machine-readable reason (`length`, `email`, `password_breached`, ...).
+    pub code: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            code: code.into(),
+            message: message.into(),
+        }
+    }
+}
+
+fn format_field_errors(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|e| format!("{}: {}", e.field, e.message))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 #[derive(Serialize)]
 struct ErrorBody {
     code: u16,
     message: &'static str,
     detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    errors: Option<Vec<FieldError>>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    captcha_required: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    account: Option<AccountBlock>,
+}
+
+/// Why the account cannot be used, so clients can tell the user and when it ends.
+#[derive(Serialize)]
+struct AccountBlock {
+    status: AccountStatus,
+    reason: Option<String>,
+    until: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cancel_token: Option<String>,
 }
 
 impl IntoResponse for ApiError {
@@ -37,7 +110,43 @@ impl IntoResponse for ApiError {
                 "Validation Failed",
                 Some(msg.clone()),
             ),
-            ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized", None),
+            ApiError::InvalidFields(errors) => (
+                StatusCode::BAD_REQUEST,
+                "Validation Failed",
+                Some(format_field_errors(errors)),
+            ),
+            ApiError::Unauthorized | ApiError::CaptchaRequired => (StatusCode::UNAUTHORIZED, "Unauthorized", None),
+            ApiError::TooManyAttempts { retry_after_secs, .. } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "Too Many Attempts",
+                Some(format!("retry in {retry_after_secs} seconds")),
+            ),
+            ApiError::Forbidden => (StatusCode::FORBIDDEN, "Forbidden", None),
+            ApiError::EmailNotVerified => (
+                StatusCode::FORBIDDEN,
+                "Email Not Verified",
+                Some("verify your email address, then refresh your tokens".into()),
+            ),
+            ApiError::MfaRequired => (
+                StatusCode::FORBIDDEN,
+                "MFA Required",
+                Some("enable two-factor authentication and sign in with it".into()),
+            ),
+            ApiError::ReauthenticationRequired => (
+                StatusCode::FORBIDDEN,
+                "Reauthentication Required",
+                Some("confirm with your password or a second factor, or sign in again".into()),
+            ),
+            ApiError::AccountDisabled { status, .. } => (
+                StatusCode::FORBIDDEN,
+                match status {
+                    AccountStatus::Banned => "Account Banned",
+                    AccountStatus::PendingDeletion => "Account Pending Deletion",
+                    AccountStatus::Deleted => "Account Deleted",
+                    _ => "Account Suspended",
+                },
+                None,
+            ),
             ApiError::NotFound => (StatusCode::NOT_FOUND, "Not Found", None),
             ApiError::Conflict(msg) => (StatusCode::CONFLICT, "Conflict", Some(msg.clone())),
             ApiError::ServiceUnavailable => (
@@ -56,11 +165,72 @@ impl IntoResponse for ApiError {
             code: status.as_u16(),
             message,
             detail,
+            errors: match &self {
+                ApiError::InvalidFields(errors) => Some(errors.clone()),
+                _ => None,
+            },
+            captcha_required: matches!(
+                self,
+                ApiError::CaptchaRequired | ApiError::TooManyAttempts { captcha_required: true, .. }
+            ),
+            account: match &self {
+                ApiError::AccountDisabled { status, reason, until, cancel_token } => Some(AccountBlock {
+                    status: *status,
+                    reason: reason.clone(),
+                    until: *until,
+                    cancel_token: cancel_token.clone(),
+                }),
+                _ => None,
+            },
         });
+        if let ApiError::TooManyAttempts { retry_after_secs, .. } = self {
+            return (status, [(header::RETRY_AFTER, retry_after_secs.to_string())], body).into_response();
+        }
         (status, body).into_response()
     }
 }
 
+/// `validator` failures as field errors. Errors of `#[validate(nested)]` structs are reported
+/// under the inner field name, since the nested requests are `#[serde(flatten)]`ed.
+impl From<validator::ValidationErrors> for ApiError {
+    fn from(errors: validator::ValidationErrors) -> Self {
+        fn collect(errors: &validator::ValidationErrors, out: &mut Vec<FieldError>) {
+            for (field, kind) in errors.errors() {
+                match kind {
+                    validator::ValidationErrorsKind::Field(list) => {
+                        out.extend(list.iter().map(|e| FieldError::new(*field, e.code.clone(), describe(e))));
+                    }
+                    validator::ValidationErrorsKind::Struct(nested) => collect(nested, out),
+                    validator::ValidationErrorsKind::List(items) => items.values().for_each(|nested| collect(nested, out)),
+                }
+            }
+        }
+
+        let mut out = Vec::new();
+        collect(&errors, &mut out);
+        out.sort_by(|a, b| a.field.cmp(&b.field));
+        ApiError::InvalidFields(out)
+    }
+}
+
+fn describe(error: &validator::ValidationError) -> String {
+    if let Some(message) = &error.message {
+        return message.to_string();
+    }
+    let param = |name: &str| error.params.get(name).map(|v| v.to_string());
+    match error.code.as_ref() {
+        "length" => match (param("min"), param("max"), param("equal")) {
+            (_, _, Some(equal)) => format!("length must be {equal}"),
+            (Some(min), Some(max), _) => format!("length must be between {min} and {max}"),
+            (Some(min), None, _) => format!("length must be at least {min}"),
+            (None, Some(max), _) => format!("length must be at most {max}"),
+            _ => "invalid length".to_string(),
+        },
+        "email" => "must be a valid email address".to_string(),
+        code => code.replace('_', " "),
+    }
+}
+
 impl From<sqlx::Error> for ApiError {
     fn from(e: sqlx::Error) -> Self {
         match e {
@@ -82,6 +252,13 @@ impl From<sqlx::Error> for ApiError {
     }
 }
 
+impl From<argon2::Error> for ApiError {
+    fn from(error: argon2::Error) -> Self {
+        tracing::error!(?error, "argon2 error");
+        ApiError::Internal
+    }
+}
+
 impl From<argon2::password_hash::Error> for ApiError {
     fn from(error: argon2::password_hash::Error) -> Self {
         tracing::error!(?error, "argon2 error");
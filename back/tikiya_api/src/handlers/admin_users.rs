@@ -0,0 +1,137 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::dto::{AccountStatusRequest, AdminUserPage, AdminUserResponse, ChangeRoleRequest, RevokeSessionsResponse};
+use crate::error::ApiError;
+use crate::models::{AccountStatus, Role};
+use crate::security::auth_user::{Admin, RequireRole};
+use crate::security::client_info::ClientInfo;
+use crate::services::admin_users::{AdminUserService, UserFilter};
+use crate::services::pagination::DEFAULT_PAGE_SIZE;
+use crate::state::AppState;
+
+#[derive(Deserialize)]
+pub struct ListUsersQuery {
+    email: Option<String>,
+    role: Option<Role>,
+    status: Option<AccountStatus>,
+    created_after: Option<DateTime<Utc>>,
+    created_before: Option<DateTime<Utc>>,
+    cursor: Option<String>,
+    limit: Option<i64>,
+}
+
+pub async fn list_users(
+    State(state): State<AppState>,
+    RequireRole(_admin, _): RequireRole<Admin>,
+    Query(q): Query<ListUsersQuery>,
+) -> Result<Json<AdminUserPage>, ApiError> {
+    let filter = UserFilter {
+        email: q.email,
+        role: q.role,
+        status: q.status,
+        created_after: q.created_after,
+        created_before: q.created_before,
+    };
+    let service = AdminUserService::new(state);
+    let page = service
+        .list(filter, q.cursor.as_deref(), q.limit.unwrap_or(DEFAULT_PAGE_SIZE))
+        .await?;
+    Ok(Json(page))
+}
+
+pub async fn get_user(
+    State(state): State<AppState>,
+    RequireRole(_admin, _): RequireRole<Admin>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AdminUserResponse>, ApiError> {
+    let service = AdminUserService::new(state);
+    Ok(Json(service.get(id).await?))
+}
+
+pub async fn change_role(
+    State(state): State<AppState>,
+    RequireRole(admin, _): RequireRole<Admin>,
+    client: ClientInfo,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ChangeRoleRequest>,
+) -> Result<Json<AdminUserResponse>, ApiError> {
+    let service = AdminUserService::new(state);
+    Ok(Json(service.change_role(admin.id, &client, id, payload.role).await?))
+}
+
+pub async fn unlock_user(
+    State(state): State<AppState>,
+    RequireRole(admin, _): RequireRole<Admin>,
+    client: ClientInfo,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AdminUserResponse>, ApiError> {
+    let service = AdminUserService::new(state);
+    Ok(Json(service.unlock(admin.id, &client, id).await?))
+}
+
+pub async fn suspend_user(
+    State(state): State<AppState>,
+    RequireRole(admin, _): RequireRole<Admin>,
+    client: ClientInfo,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<AccountStatusRequest>,
+) -> Result<Json<AdminUserResponse>, ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    let service = AdminUserService::new(state);
+    let user = service
+        .set_status(admin.id, &client, id, AccountStatus::Suspended, Some(&payload.reason), payload.until)
+        .await?;
+    Ok(Json(user))
+}
+
+pub async fn ban_user(
+    State(state): State<AppState>,
+    RequireRole(admin, _): RequireRole<Admin>,
+    client: ClientInfo,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<AccountStatusRequest>,
+) -> Result<Json<AdminUserResponse>, ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    let service = AdminUserService::new(state);
+    let user = service
+        .set_status(admin.id, &client, id, AccountStatus::Banned, Some(&payload.reason), payload.until)
+        .await?;
+    Ok(Json(user))
+}
+
+pub async fn reactivate_user(
+    State(state): State<AppState>,
+    RequireRole(admin, _): RequireRole<Admin>,
+    client: ClientInfo,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AdminUserResponse>, ApiError> {
+    let service = AdminUserService::new(state);
+    let user = service
+        .set_status(admin.id, &client, id, AccountStatus::Active, None, None)
+        .await?;
+    Ok(Json(user))
+}
+
+pub async fn revoke_user_sessions(
+    State(state): State<AppState>,
+    RequireRole(admin, _): RequireRole<Admin>,
+    client: ClientInfo,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RevokeSessionsResponse>, ApiError> {
+    let service = AdminUserService::new(state);
+    let revoked = service.revoke_sessions(admin.id, &client, id).await?;
+    Ok(Json(RevokeSessionsResponse { revoked }))
+}
@@ -0,0 +1,69 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::dto::AuditEventPage;
+use crate::error::ApiError;
+use crate::security::auth_user::{Admin, AuthUser, RequireRole};
+use crate::services::audit::{AuditFilter, AuditService};
+use crate::services::pagination::DEFAULT_PAGE_SIZE;
+use crate::state::AppState;
+
+#[derive(Deserialize)]
+pub struct PageQuery {
+    cursor: Option<String>,
+    limit: Option<i64>,
+}
+
+pub async fn my_security_events(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Query(q): Query<PageQuery>,
+) -> Result<Json<AuditEventPage>, ApiError> {
+    let service = AuditService::new(state);
+    let page = service
+        .list_for_user(user.id, q.cursor.as_deref(), q.limit.unwrap_or(DEFAULT_PAGE_SIZE))
+        .await?;
+    Ok(Json(page))
+}
+
+#[derive(Deserialize)]
+pub struct AuditEventsQuery {
+    user_id: Option<Uuid>,
+    actor_id: Option<Uuid>,
+    event_type: Option<String>,
+    outcome: Option<String>,
+    ip: Option<String>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    cursor: Option<String>,
+    limit: Option<i64>,
+}
+
+pub async fn list_audit_events(
+    State(state): State<AppState>,
+    RequireRole(_admin, _): RequireRole<Admin>,
+    Query(q): Query<AuditEventsQuery>,
+) -> Result<Json<AuditEventPage>, ApiError> {
+    if q.outcome.as_deref().is_some_and(|o| o != "success" && o != "failure") {
+        return Err(ApiError::Validation("outcome: expected success or failure".into()));
+    }
+    let filter = AuditFilter {
+        subject_user_id: q.user_id,
+        actor_user_id: q.actor_id,
+        event_type: q.event_type,
+        outcome: q.outcome,
+        ip: q.ip,
+        from: q.from,
+        to: q.to,
+    };
+    let service = AuditService::new(state);
+    let page = service
+        .query(filter, q.cursor.as_deref(), q.limit.unwrap_or(DEFAULT_PAGE_SIZE))
+        .await?;
+    Ok(Json(page))
+}
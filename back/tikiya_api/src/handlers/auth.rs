@@ -1,164 +1,152 @@
-use axum::{extract::{State, ConnectInfo}, Json};
+use axum::{extract::{Path, State}, http::StatusCode, Json};
 use serde::Deserialize;
-use std::net::SocketAddr;
 use crate::dto::AuthTokens;
 use validator::Validate;
 
-use crate::dto::{AuthResponse, LoginRequest, LogoutRequest, RefreshRequest, RegisterRequest};
+use crate::dto::{AuthResponse, CancelDeletionRequest, LoginRequest, LoginResponse, LogoutRequest, MfaLoginRequest, RefreshRequest, RegisterRequest, UserResponse, VerifyEmailRequest};
 use crate::error::ApiError;
+use crate::security::auth_user::AuthUser;
+use crate::security::client_info::ClientInfo;
+use crate::services::account::AccountService;
 use crate::services::auth::AuthService;
+use crate::services::email_verification::EmailVerificationService;
+use crate::services::mfa::MfaService;
 use crate::services::oauth::OAuthService;
 use crate::state::AppState;
 
 pub async fn register(
     State(state): State<AppState>,
-    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    client: ClientInfo,
     Json(payload): Json<RegisterRequest>,
 ) -> Result<Json<AuthResponse>, ApiError> {
-    tracing::info!(ip = %addr.ip(), "auth.register.request");
+    tracing::info!(ip = ?client.ip, "auth.register.request");
     payload
         .validate()
-        .map_err(|err| ApiError::Validation(err.to_string()))?;
+        .map_err(ApiError::from)?;
 
     let service = AuthService::new(state);
-    let response = service.register(payload).await?;
-    tracing::info!(ip = %addr.ip(), user_email = %response.user.email, "auth.register.response_success");
+    let response = service.register(payload, &client).await?;
+    tracing::info!(ip = ?client.ip, user_email = ?response.user.email, "auth.register.response_success");
 
     Ok(Json(response))
 }
 
 pub async fn login(
     State(state): State<AppState>,
-    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    client: ClientInfo,
     Json(payload): Json<LoginRequest>,
-) -> Result<Json<AuthResponse>, ApiError> {
-    tracing::info!(ip = %addr.ip(), email = %payload.email, "auth.login.request");
+) -> Result<Json<LoginResponse>, ApiError> {
+    tracing::info!(ip = ?client.ip, email = %payload.email, "auth.login.request");
     payload
         .validate()
-        .map_err(|err| ApiError::Validation(err.to_string()))?;
+        .map_err(ApiError::from)?;
     let service = AuthService::new(state);
-    let response = service.login(payload).await?;
-    tracing::info!(ip = %addr.ip(), user_email = %response.user.email, "auth.login.response_success");
+    let response = service.login(payload, &client).await?;
+    if let LoginResponse::Authenticated(auth) = &response {
+        tracing::info!(ip = ?client.ip, user_email = ?auth.user.email, "auth.login.response_success");
+    }
+    Ok(Json(response))
+}
+
+pub async fn login_mfa(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(payload): Json<MfaLoginRequest>,
+) -> Result<Json<AuthResponse>, ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    let service = MfaService::new(state);
+    let response = service.complete_login(payload, &client).await?;
+    tracing::info!(ip = ?client.ip, user_email = ?response.user.email, "auth.login_mfa.response_success");
     Ok(Json(response))
 }
 
+/// Cancels the deletion of an account in its grace period and signs it in.
+pub async fn cancel_deletion(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(payload): Json<CancelDeletionRequest>,
+) -> Result<Json<AuthResponse>, ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    let service = AccountService::new(state);
+    Ok(Json(service.cancel_deletion(&payload.cancel_token, &client).await?))
+}
+
 pub async fn refresh(
     State(state): State<AppState>,
+    client: ClientInfo,
     Json(payload): Json<RefreshRequest>,
 ) -> Result<Json<AuthTokens>, ApiError> {
     payload
         .validate()
-        .map_err(|err| ApiError::Validation(err.to_string()))?;
+        .map_err(ApiError::from)?;
 
     let service = AuthService::new(state);
-    let tokens = service.refresh(payload).await?;
+    let tokens = service.refresh(payload, &client).await?;
 
     Ok(Json(tokens))
 }
 
 pub async fn logout(
     State(state): State<AppState>,
+    client: ClientInfo,
     Json(payload): Json<LogoutRequest>,
 ) -> Result<(), ApiError> {
     payload
         .validate()
-        .map_err(|err| ApiError::Validation(err.to_string()))?;
+        .map_err(ApiError::from)?;
 
     let service = AuthService::new(state);
-    service.logout(payload).await
+    service.logout(payload, &client).await
 }
 
-#[derive(Deserialize)]
-pub struct GoogleMobileRequest {
-    id_token: String,
-}
+pub async fn verify_email(
+    State(state): State<AppState>,
+    Json(payload): Json<VerifyEmailRequest>,
+) -> Result<Json<UserResponse>, ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
 
-#[derive(serde::Deserialize)]
-struct TokenInfo {
-    aud: String,
-    iss: Option<String>,
-    exp: Option<i64>,
-    sub: String,
-    email: Option<String>,
-    email_verified: Option<String>,
+    let service = EmailVerificationService::new(state);
+    let user = service.verify(&payload.token).await?;
+    Ok(Json(UserResponse::from(&user)))
 }
 
-pub async fn google_mobile(
+pub async fn resend_verification(
     State(state): State<AppState>,
-    ConnectInfo(addr): ConnectInfo<SocketAddr>,
-    Json(payload): Json<GoogleMobileRequest>,
-) -> Result<Json<AuthResponse>, ApiError> {
-    tracing::info!(ip = %addr.ip(), "auth.google_mobile.request");
-    let client = reqwest::Client::builder()
-        .user_agent("tikiya-api/1.0")
-        .connect_timeout(std::time::Duration::from_secs(5))
-        .timeout(std::time::Duration::from_secs(10))
-        .build()
-        .map_err(|_| ApiError::Internal)?;
-    let res = client
-        .get("https://oauth2.googleapis.com/tokeninfo")
-        .query(&[("id_token", payload.id_token.clone())])
-        .send()
-        .await
-        .map_err(|e| {
-            tracing::error!(?e, "auth.google_mobile.tokeninfo_request_failed");
-            ApiError::Internal
-        })?;
-    if !res.status().is_success() {
-        let status = res.status();
-        let text = res.text().await.unwrap_or_default();
-        tracing::warn!(status = %status, body = %text, "auth.google_mobile.tokeninfo_failed");
-        return Err(ApiError::Unauthorized);
-    }
-    let info: TokenInfo = res.json().await.map_err(|e| {
-        tracing::error!(?e, "auth.google_mobile.tokeninfo_parse_failed");
-        ApiError::Internal
-    })?;
-    if info.aud != state.config.google_client_id {
-        tracing::warn!(expected = %state.config.google_client_id, got = %info.aud, "auth.google_mobile.audience_mismatch");
-        return Err(ApiError::Unauthorized);
-    }
-
-    if let Some(iss) = info.iss.as_deref() {
-        if iss != "https://accounts.google.com" && iss != "accounts.google.com" {
-            tracing::warn!(got = %iss, "auth.google_mobile.issuer_mismatch");
-            return Err(ApiError::Unauthorized);
-        }
-    }
-
-    if let Some(exp) = info.exp {
-        if exp <= chrono::Utc::now().timestamp() {
-            tracing::warn!("auth.google_mobile.token_expired");
-            return Err(ApiError::Unauthorized);
-        }
-    }
+    user: AuthUser,
+) -> Result<StatusCode, ApiError> {
+    let service = EmailVerificationService::new(state);
+    service.resend(user.id).await?;
+    Ok(StatusCode::ACCEPTED)
+}
 
-    let email = info.email.clone().unwrap_or_default();
-    if email.trim().is_empty() {
-        tracing::warn!("auth.google_mobile.missing_email");
-        return Err(ApiError::Unauthorized);
-    }
+#[derive(Deserialize)]
+pub struct MobileSignInRequest {
+    id_token: String,
+    /// Nonce the app passed to the provider's sign-in SDK, if any.
+    nonce: Option<String>,
+}
 
-    if let Some(v) = info.email_verified.as_deref() {
-        // tokeninfo returns strings like "true"/"false"
-        if v != "true" {
-            tracing::warn!(got = %v, "auth.google_mobile.email_not_verified");
-            return Err(ApiError::Unauthorized);
-        }
-    }
-    // Upsert user using OAuthService helper
+/// Sign-in with an ID token from a native SDK (Google, Apple, ...): `POST /auth/{provider}/mobile`.
+pub async fn provider_mobile(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    client: ClientInfo,
+    Json(payload): Json<MobileSignInRequest>,
+) -> Result<Json<LoginResponse>, ApiError> {
+    tracing::info!(ip = ?client.ip, provider = %provider, "auth.provider_mobile.request");
     let oauth = OAuthService::new(state.clone());
-    let user = oauth
-        .upsert_oauth_user(&crate::services::oauth::GoogleUserInfo {
-            sub: info.sub,
-            email,
-            _email_verified: true,
-            _given_name: None,
-            _family_name: None,
-        })
-        .await?;
+    let info = oauth.verify_id_token(&provider, &payload.id_token, payload.nonce.as_deref()).await?;
+    let user = oauth.upsert_oauth_user(&provider, &info, &client).await?;
     let auth = AuthService::new(state);
-    let tokens = auth.issue_tokens(&user).await?;
-    tracing::info!(ip = %addr.ip(), user_email = %user.email, "auth.google_mobile.response_success");
-    Ok(Json(AuthResponse { user: crate::dto::UserResponse::from(&user), tokens }))
+    let response = auth.complete_login(&user, &client, &format!("oauth:{provider}"), false).await?;
+    tracing::info!(ip = ?client.ip, provider = %provider, user_email = ?user.email, "auth.provider_mobile.response_success");
+    Ok(Json(response))
 }
@@ -0,0 +1,54 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use validator::Validate;
+
+use crate::dto::{IdentityResponse, LinkIdentityRequest};
+use crate::error::ApiError;
+use crate::security::auth_user::AuthUser;
+use crate::security::client_info::ClientInfo;
+use crate::services::identities::IdentityService;
+use crate::services::oauth::OAuthService;
+use crate::services::reauth::ReauthService;
+use crate::state::AppState;
+
+pub async fn list_identities(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<Vec<IdentityResponse>>, ApiError> {
+    let service = IdentityService::new(state);
+    Ok(Json(service.list(user.id).await?))
+}
+
+pub async fn link_identity(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path(provider): Path<String>,
+    Json(payload): Json<LinkIdentityRequest>,
+) -> Result<Json<Vec<IdentityResponse>>, ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    ReauthService::new(state.clone()).verify(&user, &client, &payload.reauth).await?;
+    let info = OAuthService::new(state.clone())
+        .verify_id_token(&provider, &payload.id_token, payload.nonce.as_deref())
+        .await?;
+    let service = IdentityService::new(state);
+    service.link(user.id, &provider, &info, &client).await?;
+    Ok(Json(service.list(user.id).await?))
+}
+
+pub async fn unlink_identity(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path(provider): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let service = IdentityService::new(state);
+    service.unlink(user.id, &provider, &client).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,16 @@
+use axum::{
+    extract::State,
+    http::header,
+    response::IntoResponse,
+    Json,
+};
+
+use crate::state::AppState;
+
+/// Public keys accepted for access tokens, so other services can verify them without a secret.
+pub async fn jwks(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CACHE_CONTROL, "public, max-age=300")],
+        Json(state.jwt_keys.jwks().clone()),
+    )
+}
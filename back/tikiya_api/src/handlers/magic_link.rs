@@ -0,0 +1,41 @@
+use axum::{extract::State, http::StatusCode, Json};
+use validator::Validate;
+
+use crate::dto::{LoginResponse, MagicLinkRequest, MagicLinkVerifyRequest};
+use crate::error::ApiError;
+use crate::security::client_info::ClientInfo;
+use crate::services::magic_link::MagicLinkService;
+use crate::state::AppState;
+
+pub async fn request_magic_link(
+    State(state): State<AppState>,
+    Json(payload): Json<MagicLinkRequest>,
+) -> Result<StatusCode, ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    // Same status and timing whether the address is known, new or rate-limited.
+    tokio::spawn(async move {
+        let service = MagicLinkService::new(state);
+        if let Err(err) = service.send(&payload.email).await {
+            tracing::error!(error = %err, "auth.magic_link.send_failed");
+        }
+    });
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+pub async fn verify_magic_link(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(payload): Json<MagicLinkVerifyRequest>,
+) -> Result<Json<LoginResponse>, ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    let service = MagicLinkService::new(state);
+    let response = service.verify(&payload.token, &client).await?;
+    Ok(Json(response))
+}
@@ -1,66 +1,72 @@
-use axum::{extract::State, http::HeaderMap, Json};
-use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
-use serde::Deserialize;
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use validator::Validate;
 
-use crate::{dto::UserResponse, error::ApiError, state::AppState};
+use crate::{
+    dto::{AccountDeletionResponse, DeleteAccountRequest, UpdateProfileRequest, UserResponse},
+    error::ApiError,
+    security::auth_user::{Admin, AuthUser, RequireRole},
+    security::client_info::ClientInfo,
+    services::{account::AccountService, profile::ProfileService, reauth::ReauthService},
+    state::AppState,
+};
 
-#[derive(Debug, Deserialize)]
-struct JwtClaims {
-    sub: uuid::Uuid,
-    _email: String,
-    _exp: usize,
-    _iat: usize,
-    _aud: String,
-    _iss: String,
+pub async fn me(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<UserResponse>, ApiError> {
+    let service = ProfileService::new(state);
+    Ok(Json(service.get(user.id).await?))
 }
 
-pub async fn admin_me(
+pub async fn update_me(
     State(state): State<AppState>,
-    headers: HeaderMap,
+    user: AuthUser,
+    Json(payload): Json<UpdateProfileRequest>,
 ) -> Result<Json<UserResponse>, ApiError> {
-    let auth = headers
-        .get("authorization")
-        .and_then(|v| v.to_str().ok())
-        .ok_or(ApiError::Unauthorized)?;
-
-    let token = auth.strip_prefix("Bearer ").ok_or(ApiError::Unauthorized)?;
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
 
-    let mut validation = Validation::new(Algorithm::HS256);
-    validation.set_audience(std::slice::from_ref(&state.config.jwt_audience));
-    validation.set_issuer(std::slice::from_ref(&state.config.jwt_issuer));
-
-    let claims = decode::<JwtClaims>(
-        token,
-        &DecodingKey::from_secret(state.config.jwt_secret.as_bytes()),
-        &validation,
-    )
-    .map_err(|_| ApiError::Unauthorized)?
-    .claims;
+    let service = ProfileService::new(state);
+    Ok(Json(service.update(user.id, payload).await?))
+}
 
-    // Load user and check role
-    #[derive(sqlx::FromRow)]
-    struct MeRow {
-        id: uuid::Uuid,
-        email: String,
-        role: String,
-        created_at: chrono::DateTime<chrono::Utc>,
-    }
+/// Personal data export, served as a JSON attachment.
+pub async fn export_me(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<impl IntoResponse, ApiError> {
+    let service = AccountService::new(state);
+    let export = service.export(user.id).await?;
+    let disposition = format!("attachment; filename=\"tikiya-export-{}.json\"", user.id);
+    Ok(([(header::CONTENT_DISPOSITION, disposition)], Json(export)))
+}
 
-    let user = sqlx::query_as::<_, MeRow>(
-        "SELECT id, email, role, created_at FROM users WHERE id = $1",
-    )
-    .bind(claims.sub)
-    .fetch_one(&state.db.pool)
-    .await?;
+pub async fn delete_me(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<DeleteAccountRequest>,
+) -> Result<(StatusCode, Json<AccountDeletionResponse>), ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
 
-    if user.role != "admin" {
-        return Err(ApiError::Unauthorized);
-    }
+    ReauthService::new(state.clone()).verify(&user, &client, &payload.reauth).await?;
+    let service = AccountService::new(state);
+    let scheduled = service.schedule_deletion(user.id, &client).await?;
+    Ok((StatusCode::ACCEPTED, Json(scheduled)))
+}
 
-    Ok(Json(UserResponse {
-        id: user.id,
-        email: user.email,
-        role: user.role,
-        created_at: user.created_at,
-    }))
+pub async fn admin_me(
+    State(state): State<AppState>,
+    RequireRole(admin, _): RequireRole<Admin>,
+) -> Result<Json<UserResponse>, ApiError> {
+    let service = ProfileService::new(state);
+    Ok(Json(service.get(admin.id).await?))
 }
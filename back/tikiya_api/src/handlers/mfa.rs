@@ -0,0 +1,73 @@
+use axum::{extract::State, http::StatusCode, Json};
+use validator::Validate;
+
+use crate::dto::{ConfirmTotpRequest, MfaCodeRequest, ReauthRequest, RecoveryCodesResponse, TotpEnrollmentResponse};
+use crate::error::ApiError;
+use crate::security::auth_user::AuthUser;
+use crate::security::client_info::ClientInfo;
+use crate::services::mfa::MfaService;
+use crate::services::reauth::ReauthService;
+use crate::state::AppState;
+
+/// Enrolling an authenticator changes how the account signs in, so it takes a re-authentication
+/// (see [`ReauthService::verify`]), as passkey enrolment does.
+pub async fn start_totp(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<ReauthRequest>,
+) -> Result<Json<TotpEnrollmentResponse>, ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    ReauthService::new(state.clone()).verify(&user, &client, &payload).await?;
+    let service = MfaService::new(state);
+    Ok(Json(service.start_totp_enrollment(user.id, &user.account_name()).await?))
+}
+
+pub async fn confirm_totp(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<ConfirmTotpRequest>,
+) -> Result<Json<RecoveryCodesResponse>, ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    let service = MfaService::new(state);
+    let codes = service
+        .confirm_totp_enrollment(user.id, &user.account_name(), user.session_id, &payload.code, &client)
+        .await?;
+    Ok(Json(codes))
+}
+
+pub async fn disable_totp(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<MfaCodeRequest>,
+) -> Result<StatusCode, ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    let service = MfaService::new(state);
+    service.disable_totp(user.id, &user.account_name(), &payload, &client).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn regenerate_recovery_codes(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<MfaCodeRequest>,
+) -> Result<Json<RecoveryCodesResponse>, ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    let service = MfaService::new(state);
+    Ok(Json(service.regenerate_recovery_codes(user.id, &user.account_name(), &payload, &client).await?))
+}
@@ -1,4 +1,15 @@
+pub mod admin_users;
+pub mod audit;
 pub mod auth;
 pub use auth::{login, register};
+pub mod identities;
+pub mod jwks;
+pub mod magic_link;
 pub mod oauth;
+pub mod organizer;
 pub mod me;
+pub mod mfa;
+pub mod password;
+pub mod phone;
+pub mod sessions;
+pub mod webauthn;
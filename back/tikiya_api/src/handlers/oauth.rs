@@ -1,16 +1,16 @@
-use axum::{extract::{Query, State}, Json};
+use axum::{extract::{Path, Query, State}, Form, Json};
 use serde::Deserialize;
 
+use crate::dto::OAuthCallbackResponse;
 use crate::error::ApiError;
-use crate::security::oauth_state;
+use crate::security::client_info::ClientInfo;
 use crate::services::oauth::OAuthService;
 use crate::state::AppState;
 
 #[derive(Deserialize)]
 pub struct StartQuery {
+    /// Opaque caller state, returned as `client_state` by the callback.
     state: Option<String>,
-    code_challenge: Option<String>,
-    code_challenge_method: Option<String>,
 }
 
 #[derive(serde::Serialize)]
@@ -18,28 +18,48 @@ pub struct AuthUrlResponse {
     url: String,
 }
 
-pub async fn google_start(State(state): State<AppState>, Query(q): Query<StartQuery>) -> Result<Json<AuthUrlResponse>, ApiError> {
-    let signed_state = oauth_state::mint_state(&state.config.jwt_secret, q.state.as_deref())?;
+pub async fn provider_start(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    client: ClientInfo,
+    Query(q): Query<StartQuery>,
+) -> Result<Json<AuthUrlResponse>, ApiError> {
+    if q.state.as_ref().is_some_and(|s| s.len() > 512) {
+        return Err(ApiError::Validation("state: too long".into()));
+    }
     let svc = OAuthService::new(state);
-    let url = svc.google_auth_url(
-        &signed_state,
-        q.code_challenge.as_deref(),
-        q.code_challenge_method.as_deref(),
-    ).map_err(|_| ApiError::Internal)?;
-    Ok(Json(AuthUrlResponse { url: url.to_string() }))
+    let url = svc.auth_url(&provider, q.state.as_deref(), &client).await?;
+    Ok(Json(AuthUrlResponse { url }))
 }
 
 #[derive(Deserialize)]
 pub struct CallbackQuery {
     code: String,
     state: Option<String>,
-    code_verifier: Option<String>,
 }
 
-pub async fn google_callback(State(state): State<AppState>, Query(q): Query<CallbackQuery>) -> Result<axum::Json<crate::dto::AuthResponse>, ApiError> {
+pub async fn provider_callback(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    client: ClientInfo,
+    Query(q): Query<CallbackQuery>,
+) -> Result<Json<OAuthCallbackResponse>, ApiError> {
+    callback(state, &provider, &client, q).await
+}
+
+/// `response_mode=form_post` callback (Apple).
+pub async fn provider_callback_form(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    client: ClientInfo,
+    Form(q): Form<CallbackQuery>,
+) -> Result<Json<OAuthCallbackResponse>, ApiError> {
+    callback(state, &provider, &client, q).await
+}
+
+async fn callback(state: AppState, provider: &str, client: &ClientInfo, q: CallbackQuery) -> Result<Json<OAuthCallbackResponse>, ApiError> {
     let state_str = q.state.as_deref().ok_or(ApiError::Unauthorized)?;
-    oauth_state::verify_state(&state.config.jwt_secret, state_str)?;
     let svc = OAuthService::new(state);
-    let resp = svc.google_callback(&q.code, q.code_verifier.as_deref()).await?;
+    let resp = svc.callback(provider, &q.code, state_str, client).await?;
     Ok(Json(resp))
 }
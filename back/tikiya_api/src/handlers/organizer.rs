@@ -0,0 +1,87 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::dto::{ApproveApplicationRequest, OrganizerApplicationRequest, OrganizerApplicationResponse, RejectApplicationRequest};
+use crate::error::ApiError;
+use crate::security::auth_user::{Admin, AuthUser, RequireRole};
+use crate::security::client_info::ClientInfo;
+use crate::services::organizer::OrganizerService;
+use crate::state::AppState;
+
+pub async fn apply(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(payload): Json<OrganizerApplicationRequest>,
+) -> Result<(StatusCode, Json<OrganizerApplicationResponse>), ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    let service = OrganizerService::new(state);
+    let application = service.apply(user.id, user.role, payload).await?;
+    Ok((StatusCode::CREATED, Json(application)))
+}
+
+pub async fn my_application(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<OrganizerApplicationResponse>, ApiError> {
+    let service = OrganizerService::new(state);
+    Ok(Json(service.latest_for_user(user.id).await?))
+}
+
+#[derive(Deserialize)]
+pub struct ListApplicationsQuery {
+    status: Option<String>,
+}
+
+pub async fn list_applications(
+    State(state): State<AppState>,
+    RequireRole(_admin, _): RequireRole<Admin>,
+    Query(q): Query<ListApplicationsQuery>,
+) -> Result<Json<Vec<OrganizerApplicationResponse>>, ApiError> {
+    // Pending applications by default; `all` lifts the filter.
+    let status = match q.status.as_deref().unwrap_or("pending") {
+        "all" => None,
+        s @ ("pending" | "approved" | "rejected") => Some(s),
+        _ => return Err(ApiError::Validation("status: expected pending, approved, rejected or all".into())),
+    };
+    let service = OrganizerService::new(state);
+    Ok(Json(service.list(status).await?))
+}
+
+pub async fn approve_application(
+    State(state): State<AppState>,
+    RequireRole(admin, _): RequireRole<Admin>,
+    client: ClientInfo,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ApproveApplicationRequest>,
+) -> Result<Json<OrganizerApplicationResponse>, ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    let service = OrganizerService::new(state);
+    Ok(Json(service.approve(admin.id, &client, id, payload.note.as_deref()).await?))
+}
+
+pub async fn reject_application(
+    State(state): State<AppState>,
+    RequireRole(admin, _): RequireRole<Admin>,
+    client: ClientInfo,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<RejectApplicationRequest>,
+) -> Result<Json<OrganizerApplicationResponse>, ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    let service = OrganizerService::new(state);
+    Ok(Json(service.reject(admin.id, &client, id, &payload.reason).await?))
+}
@@ -0,0 +1,60 @@
+use axum::{extract::State, http::StatusCode, Json};
+use validator::Validate;
+
+use crate::dto::{ChangePasswordRequest, ForgotPasswordRequest, ResetPasswordRequest};
+use crate::error::ApiError;
+use crate::security::auth_user::AuthUser;
+use crate::security::client_info::ClientInfo;
+use crate::services::auth::AuthService;
+use crate::services::password_reset::PasswordResetService;
+use crate::services::reauth::ReauthService;
+use crate::state::AppState;
+
+pub async fn forgot_password(
+    State(state): State<AppState>,
+    Json(payload): Json<ForgotPasswordRequest>,
+) -> Result<StatusCode, ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    // Run the lookup + mail off the request path: same status and timing whether the email exists or not.
+    tokio::spawn(async move {
+        let service = PasswordResetService::new(state);
+        if let Err(err) = service.forgot(&payload.email).await {
+            tracing::error!(error = %err, "auth.password_forgot.failed");
+        }
+    });
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+pub async fn reset_password(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(payload): Json<ResetPasswordRequest>,
+) -> Result<StatusCode, ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    let service = PasswordResetService::new(state);
+    service.reset(payload, &client).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn change_password(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> Result<StatusCode, ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    ReauthService::new(state.clone()).verify(&user, &client, &payload.reauth).await?;
+    let service = AuthService::new(state);
+    service.change_password(user.id, user.session_id, payload, &client).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
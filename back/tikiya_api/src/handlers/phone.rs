@@ -0,0 +1,45 @@
+use axum::{extract::State, http::StatusCode, Json};
+use validator::Validate;
+
+use crate::dto::{LoginResponse, PhoneCodeRequest, PhoneVerifyRequest};
+use crate::error::ApiError;
+use crate::security::client_info::ClientInfo;
+use crate::services::phone_auth::PhoneAuthService;
+use crate::state::AppState;
+
+pub async fn request_phone_code(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(payload): Json<PhoneCodeRequest>,
+) -> Result<StatusCode, ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    let service = PhoneAuthService::new(state);
+    let phone = service.normalize(&payload.phone)?;
+
+    // Same status whether the number is known, new or rate-limited.
+    tokio::spawn(async move {
+        if let Err(err) = service.send_code(&phone, client.ip).await {
+            tracing::error!(error = %err, "auth.phone.send_failed");
+        }
+    });
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+pub async fn verify_phone_code(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(payload): Json<PhoneVerifyRequest>,
+) -> Result<Json<LoginResponse>, ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    let service = PhoneAuthService::new(state);
+    let phone = service.normalize(&payload.phone)?;
+    let response = service.verify(&phone, &payload.code, &client).await?;
+    Ok(Json(response))
+}
@@ -0,0 +1,51 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::dto::{RevokeSessionsResponse, SessionResponse};
+use crate::error::ApiError;
+use crate::security::auth_user::AuthUser;
+use crate::security::client_info::ClientInfo;
+use crate::services::sessions::SessionService;
+use crate::state::AppState;
+
+pub async fn list_sessions(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<Vec<SessionResponse>>, ApiError> {
+    let service = SessionService::new(state);
+    Ok(Json(service.list(user.id, user.session_id).await?))
+}
+
+pub async fn revoke_session(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path(session_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    let service = SessionService::new(state);
+    service.revoke(user.id, session_id, &client).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+pub struct RevokeAllQuery {
+    #[serde(default)]
+    keep_current: bool,
+}
+
+pub async fn revoke_all_sessions(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Query(q): Query<RevokeAllQuery>,
+) -> Result<Json<RevokeSessionsResponse>, ApiError> {
+    let keep = if q.keep_current { user.session_id } else { None };
+    let service = SessionService::new(state);
+    let revoked = service.sign_out_all(user.id, keep, &client).await?;
+    Ok(Json(RevokeSessionsResponse { revoked }))
+}
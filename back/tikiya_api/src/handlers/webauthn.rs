@@ -0,0 +1,92 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::dto::{
+    LoginResponse, ReauthRequest, WebauthnCredentialResponse, WebauthnLoginFinishRequest, WebauthnLoginStartRequest,
+    WebauthnOptionsResponse, WebauthnRegisterFinishRequest,
+};
+use crate::error::ApiError;
+use crate::security::auth_user::AuthUser;
+use crate::security::client_info::ClientInfo;
+use crate::services::reauth::ReauthService;
+use crate::services::webauthn::WebauthnService;
+use crate::state::AppState;
+
+/// Enrolling a passkey adds a lasting way in, so it takes a re-authentication (see
+/// [`ReauthService::verify`]); the finish step is bound to the challenge issued here.
+pub async fn register_start(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<ReauthRequest>,
+) -> Result<Json<WebauthnOptionsResponse>, ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    ReauthService::new(state.clone()).verify(&user, &client, &payload).await?;
+    let service = WebauthnService::new(state);
+    Ok(Json(service.start_registration(user.id, &user.account_name()).await?))
+}
+
+pub async fn register_finish(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Json(payload): Json<WebauthnRegisterFinishRequest>,
+) -> Result<(StatusCode, Json<WebauthnCredentialResponse>), ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    let service = WebauthnService::new(state);
+    let credential = service.finish_registration(user.id, payload, &client).await?;
+    Ok((StatusCode::CREATED, Json(credential)))
+}
+
+pub async fn login_start(
+    State(state): State<AppState>,
+    Json(payload): Json<WebauthnLoginStartRequest>,
+) -> Result<Json<WebauthnOptionsResponse>, ApiError> {
+    payload
+        .validate()
+        .map_err(ApiError::from)?;
+
+    let service = WebauthnService::new(state);
+    Ok(Json(service.start_login(payload).await?))
+}
+
+pub async fn login_finish(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(payload): Json<WebauthnLoginFinishRequest>,
+) -> Result<Json<LoginResponse>, ApiError> {
+    tracing::info!(ip = ?client.ip, "auth.webauthn.login_request");
+    let service = WebauthnService::new(state);
+    let response = service.finish_login(payload, &client).await?;
+    Ok(Json(response))
+}
+
+pub async fn list_credentials(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<Vec<WebauthnCredentialResponse>>, ApiError> {
+    let service = WebauthnService::new(state);
+    Ok(Json(service.list_credentials(user.id).await?))
+}
+
+pub async fn delete_credential(
+    State(state): State<AppState>,
+    user: AuthUser,
+    client: ClientInfo,
+    Path(credential_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    let service = WebauthnService::new(state);
+    service.delete_credential(user.id, credential_id, &client).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -89,9 +89,13 @@ pub fn build_router(state: AppState) -> Router {
     Router::new()
         .route("/health", get(|| async { "OK" }))
         .route("/ready", get(ready))
+        .merge(routes::admin::router())
         .merge(routes::auth::router())
         .merge(routes::me::router())
         .merge(routes::oauth::router())
+        .merge(routes::organizer::router())
+        .merge(routes::webauthn::router())
+        .merge(routes::well_known::router())
         .with_state(state)
         .layer(middleware)
         .layer(from_fn_with_state(hsts_enabled, security_headers))
@@ -109,10 +113,11 @@ fn build_cors(allowed_origins: &[String]) -> CorsLayer {
 
     CorsLayer::new()
         .allow_origin(origins)
-        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE])
         .allow_headers([
             HeaderName::from_static("content-type"),
             HeaderName::from_static("authorization"),
+            HeaderName::from_static("x-device-name"),
         ])
         .expose_headers([HeaderName::from_static("etag")])
         .max_age(Duration::from_secs(60))
@@ -27,11 +27,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     tracing::info!(port = %cfg.port, origins = ?cfg.allowed_origins, "config.loaded");
 
+    let mailer = services::mail::from_config(&cfg);
+    let sms = services::sms::from_config(&cfg);
+    let jwt_keys = std::sync::Arc::new(security::jwt::JwtKeys::from_config(&cfg)?);
+    let password_policy = std::sync::Arc::new(security::password_policy::PasswordPolicy::from_config(&cfg.password_policy)?);
+    let http = reqwest::Client::builder()
+        .user_agent("tikiya-api/1.0")
+        .connect_timeout(std::time::Duration::from_secs(5))
+        .timeout(std::time::Duration::from_secs(10))
+        .build()?;
+    let oidc = std::sync::Arc::new(services::oidc::OidcRegistry::from_config(&cfg, http));
+
     let state = state::AppState {
         db,
         config: cfg.clone(),
+        mailer,
+        sms,
+        jwt_keys,
+        password_policy,
+        oidc,
     };
 
+    services::mfa::MfaService::new(state.clone())
+        .seal_plaintext_secrets()
+        .await
+        .map_err(|e| format!("encryption des secrets TOTP: {e}"))?;
+    tokio::spawn(services::account::run_purge_loop(state.clone()));
+
     let app = http::build_router(state);
 
     let addr = SocketAddr::from(([0, 0, 0, 0], cfg.port));
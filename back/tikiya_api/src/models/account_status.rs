@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Whether the account may be used, stored as the `account_status` Postgres enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "account_status", rename_all = "snake_case")]
+pub enum AccountStatus {
+    Active,
+    Suspended,
+    Banned,
+    /// The user asked for deletion; the account is erased when the grace period ends.
+    PendingDeletion,
+    /// Personal data erased; the row is kept for the records that reference it.
+    Deleted,
+}
+
+impl AccountStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountStatus::Active => "active",
+            AccountStatus::Suspended => "suspended",
+            AccountStatus::Banned => "banned",
+            AccountStatus::PendingDeletion => "pending_deletion",
+            AccountStatus::Deleted => "deleted",
+        }
+    }
+
+    /// Whether the account may sign in at `now`. A suspension or ban whose `until` has passed
+    /// no longer applies.
+    pub fn allows_sign_in(&self, until: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+        match self {
+            AccountStatus::Active => true,
+            AccountStatus::Suspended | AccountStatus::Banned => until.is_some_and(|until| until <= now),
+            AccountStatus::PendingDeletion | AccountStatus::Deleted => false,
+        }
+    }
+}
+
+impl std::fmt::Display for AccountStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
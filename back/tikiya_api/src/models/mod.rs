@@ -1,2 +1,6 @@
+pub mod account_status;
+pub mod role;
 pub mod user;
-pub use user::User;
+pub use account_status::AccountStatus;
+pub use role::Role;
+pub use user::{User, USER_COLUMNS};
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+/// Account role, stored as the `user_role` Postgres enum and embedded in access tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "user_role", rename_all = "lowercase")]
+pub enum Role {
+    Client,
+    // Older mobile builds still send/expect the French spelling.
+    #[serde(alias = "organisateur")]
+    Organizer,
+    Admin,
+    Scanner,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Client => "client",
+            Role::Organizer => "organizer",
+            Role::Admin => "admin",
+            Role::Scanner => "scanner",
+        }
+    }
+
+    /// Whether moving from `self` to `to` can take away access: any change except from `client`
+    /// or to `admin`, since roles are not strictly nested (a scanner is not an organizer).
+    pub fn loses_access_to(self, to: Role) -> bool {
+        self != to && self != Role::Client && to != Role::Admin
+    }
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "client" => Ok(Role::Client),
+            "organizer" | "organisateur" => Ok(Role::Organizer),
+            "admin" => Ok(Role::Admin),
+            "scanner" => Ok(Role::Scanner),
+            other => Err(format!("unknown role '{}'", other)),
+        }
+    }
+}
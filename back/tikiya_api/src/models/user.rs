@@ -2,15 +2,32 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::{AccountStatus, Role};
+
+/// Column list matching the `User` row layout, for `SELECT` / `RETURNING` clauses.
+pub const USER_COLUMNS: &str = "id, email, password_hash, role, created_at, failed_attempts, lockout_until, first_name, last_name, phone, preferred_language, city, email_verified_at, phone_verified_at, status, status_reason, status_until";
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct User {
     pub id: Uuid,
-    pub email: String,
+    /// `None` for accounts created by phone.
+    pub email: Option<String>,
     pub password_hash: Option<String>,
-    pub role: String,
-    pub oauth_provider: Option<String>,
-    pub oauth_subject: Option<String>,
+    pub role: Role,
     pub created_at: DateTime<Utc>,
     pub failed_attempts: i32,
     pub lockout_until: Option<DateTime<Utc>>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub phone: Option<String>,
+    pub preferred_language: Option<String>,
+    pub city: Option<String>,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    /// Set once `phone` (E.164) was confirmed by SMS code; the number then signs in.
+    pub phone_verified_at: Option<DateTime<Utc>>,
+    pub status: AccountStatus,
+    /// Why an admin suspended or banned the account.
+    pub status_reason: Option<String>,
+    /// End of a suspension or ban, or the scheduled deletion time.
+    pub status_until: Option<DateTime<Utc>>,
 }
@@ -0,0 +1,20 @@
+use axum::{routing::{get, post, put}, Router};
+
+use crate::handlers::admin_users::{
+    ban_user, change_role, get_user, list_users, reactivate_user, revoke_user_sessions, suspend_user, unlock_user,
+};
+use crate::handlers::audit::list_audit_events;
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/admin/users", get(list_users))
+        .route("/admin/users/{id}", get(get_user))
+        .route("/admin/users/{id}/role", put(change_role))
+        .route("/admin/users/{id}/unlock", post(unlock_user))
+        .route("/admin/users/{id}/suspend", post(suspend_user))
+        .route("/admin/users/{id}/ban", post(ban_user))
+        .route("/admin/users/{id}/reactivate", post(reactivate_user))
+        .route("/admin/users/{id}/sessions/revoke", post(revoke_user_sessions))
+        .route("/admin/audit-events", get(list_audit_events))
+}
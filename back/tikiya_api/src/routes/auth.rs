@@ -1,13 +1,23 @@
 use axum::{routing::post, Router};
 
-use crate::handlers::{self, auth::logout, auth::refresh, auth::google_mobile};
+use crate::handlers::{self, auth::cancel_deletion, auth::login_mfa, auth::logout, auth::refresh, auth::provider_mobile, auth::resend_verification, auth::verify_email, magic_link::{request_magic_link, verify_magic_link}, password::{forgot_password, reset_password}, phone::{request_phone_code, verify_phone_code}};
 use crate::state::AppState;
 
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/register", post(handlers::register))
         .route("/login", post(handlers::login))
-    .route("/auth/google/mobile", post(google_mobile))
+        .route("/login/mfa", post(login_mfa))
+        .route("/auth/deletion/cancel", post(cancel_deletion))
+        .route("/auth/magic-link", post(request_magic_link))
+        .route("/auth/magic-link/verify", post(verify_magic_link))
+        .route("/auth/phone", post(request_phone_code))
+        .route("/auth/phone/verify", post(verify_phone_code))
+        .route("/auth/{provider}/mobile", post(provider_mobile))
         .route("/refresh", post(refresh))
         .route("/logout", post(logout))
+        .route("/verify-email", post(verify_email))
+        .route("/verify-email/resend", post(resend_verification))
+        .route("/password/forgot", post(forgot_password))
+        .route("/password/reset", post(reset_password))
 }
@@ -1,8 +1,26 @@
-use axum::{routing::get, Router};
+use axum::{routing::{delete, get, post}, Router};
 
-use crate::handlers::me::admin_me;
+use crate::handlers::audit::my_security_events;
+use crate::handlers::identities::{link_identity, list_identities, unlink_identity};
+use crate::handlers::me::{admin_me, delete_me, export_me, me, update_me};
+use crate::handlers::mfa::{confirm_totp, disable_totp, regenerate_recovery_codes, start_totp};
+use crate::handlers::password::change_password;
+use crate::handlers::sessions::{list_sessions, revoke_all_sessions, revoke_session};
 use crate::state::AppState;
 
 pub fn router() -> Router<AppState> {
-    Router::new().route("/me/admin", get(admin_me))
+    Router::new()
+        .route("/me", get(me).patch(update_me).delete(delete_me))
+        .route("/me/export", get(export_me))
+        .route("/me/security-events", get(my_security_events))
+        .route("/me/password", post(change_password))
+        .route("/me/sessions", get(list_sessions))
+        .route("/me/sessions/revoke-all", post(revoke_all_sessions))
+        .route("/me/sessions/{id}", delete(revoke_session))
+        .route("/me/identities", get(list_identities))
+        .route("/me/identities/{provider}", post(link_identity).delete(unlink_identity))
+        .route("/me/mfa/totp", post(start_totp).delete(disable_totp))
+        .route("/me/mfa/totp/confirm", post(confirm_totp))
+        .route("/me/mfa/recovery-codes", post(regenerate_recovery_codes))
+        .route("/me/admin", get(admin_me))
 }
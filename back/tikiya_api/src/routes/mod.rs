@@ -1,3 +1,7 @@
+pub mod admin;
 pub mod auth;
 pub mod oauth;
 pub mod me;
+pub mod organizer;
+pub mod webauthn;
+pub mod well_known;
@@ -1,10 +1,10 @@
 use axum::{routing::get, Router};
 
-use crate::handlers::oauth::{google_callback, google_start};
+use crate::handlers::oauth::{provider_callback, provider_callback_form, provider_start};
 use crate::state::AppState;
 
 pub fn router() -> Router<AppState> {
     Router::new()
-        .route("/auth/google", get(google_start))
-        .route("/auth/google/callback", get(google_callback))
+        .route("/auth/{provider}", get(provider_start))
+        .route("/auth/{provider}/callback", get(provider_callback).post(provider_callback_form))
 }
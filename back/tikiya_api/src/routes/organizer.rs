@@ -0,0 +1,13 @@
+use axum::{routing::{get, post}, Router};
+
+use crate::handlers::organizer::{apply, approve_application, list_applications, my_application, reject_application};
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/organizer/applications", post(apply))
+        .route("/me/organizer-application", get(my_application))
+        .route("/admin/organizer-applications", get(list_applications))
+        .route("/admin/organizer-applications/{id}/approve", post(approve_application))
+        .route("/admin/organizer-applications/{id}/reject", post(reject_application))
+}
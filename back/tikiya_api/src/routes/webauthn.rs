@@ -0,0 +1,14 @@
+use axum::{routing::{delete, get, post}, Router};
+
+use crate::handlers::webauthn::{delete_credential, list_credentials, login_finish, login_start, register_finish, register_start};
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/webauthn/register/start", post(register_start))
+        .route("/webauthn/register/finish", post(register_finish))
+        .route("/webauthn/login/start", post(login_start))
+        .route("/webauthn/login/finish", post(login_finish))
+        .route("/me/webauthn/credentials", get(list_credentials))
+        .route("/me/webauthn/credentials/{id}", delete(delete_credential))
+}
@@ -0,0 +1,8 @@
+use axum::{routing::get, Router};
+
+use crate::handlers::jwks::jwks;
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/.well-known/jwks.json", get(jwks))
+}
@@ -0,0 +1,131 @@
+use std::marker::PhantomData;
+
+use axum::{extract::FromRequestParts, http::request::Parts};
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::models::Role;
+use crate::services::auth::decode_access_token;
+use crate::state::AppState;
+
+/// Authenticated caller, resolved from the `Authorization: Bearer <access_token>` header.
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub id: Uuid,
+    pub email: Option<String>,
+    pub role: Role,
+    pub email_verified: bool,
+    pub session_id: Option<Uuid>,
+    /// The session was authenticated with a second factor.
+    pub mfa: bool,
+}
+
+impl AuthUser {
+    /// Account label shown in authenticator apps and passkey pickers: the email, or the user id
+    /// for phone-only accounts.
+    pub fn account_name(&self) -> String {
+        self.email.clone().unwrap_or_else(|| self.id.to_string())
+    }
+}
+
+impl FromRequestParts<AppState> for AuthUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .ok_or(ApiError::Unauthorized)?;
+
+        let claims = decode_access_token(state, token)?;
+
+        Ok(Self {
+            id: claims.sub,
+            email: claims.email,
+            role: claims.role,
+            email_verified: claims.email_verified,
+            session_id: claims.sid,
+            mfa: claims.mfa,
+        })
+    }
+}
+
+/// [`AuthUser`] whose email address has been verified (ticket purchase and similar actions).
+/// Relies on the `email_verified` claim: clients refresh their tokens after verifying.
+#[allow(dead_code)] // no purchase route yet
+pub struct VerifiedUser(pub AuthUser);
+
+impl FromRequestParts<AppState> for VerifiedUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let user = AuthUser::from_request_parts(parts, state).await?;
+        if !user.email_verified {
+            return Err(ApiError::EmailNotVerified);
+        }
+        Ok(Self(user))
+    }
+}
+
+/// Marker for a set of roles allowed through [`RequireRole`].
+pub trait RoleGuard {
+    const ROLES: &'static [Role];
+    const REQUIRES_VERIFIED_EMAIL: bool = false;
+}
+
+pub struct Admin;
+
+impl RoleGuard for Admin {
+    const ROLES: &'static [Role] = &[Role::Admin];
+}
+
+#[allow(dead_code)] // no organizer-only route yet
+pub struct Organizer;
+
+impl RoleGuard for Organizer {
+    const ROLES: &'static [Role] = &[Role::Organizer, Role::Admin];
+    const REQUIRES_VERIFIED_EMAIL: bool = true;
+}
+
+/// Same as [`AuthUser`], but rejects callers whose role is not allowed by `R` with 403, and
+/// callers of a role listed in `MFA_REQUIRED_ROLES` whose session has no second factor. Unlike
+/// [`AuthUser`], it checks the role and the session against the database.
+pub struct RequireRole<R: RoleGuard>(pub AuthUser, pub PhantomData<R>);
+
+impl<R: RoleGuard> FromRequestParts<AppState> for RequireRole<R> {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let user = AuthUser::from_request_parts(parts, state).await?;
+        if !R::ROLES.contains(&user.role) {
+            tracing::warn!(user_id = %user.id, email = ?user.email, role = %user.role, "auth.role.forbidden");
+            return Err(ApiError::Forbidden);
+        }
+        if R::REQUIRES_VERIFIED_EMAIL && !user.email_verified {
+            return Err(ApiError::EmailNotVerified);
+        }
+        if !user.mfa && state.config.mfa_required_roles.contains(&user.role) {
+            tracing::warn!(user_id = %user.id, role = %user.role, "auth.role.mfa_required");
+            return Err(ApiError::MfaRequired);
+        }
+
+        // The claim may be up to an access-token lifetime old: confirm the role is still held and
+        // the session still open, so a demotion or revocation applies at once.
+        let current_role = sqlx::query_scalar::<_, Role>(
+            "SELECT u.role FROM users u WHERE u.id = $1 AND ($2::uuid IS NULL OR EXISTS \
+             (SELECT 1 FROM sessions s WHERE s.id = $2 AND s.user_id = u.id AND s.revoked_at IS NULL))",
+        )
+        .bind(user.id)
+        .bind(user.session_id)
+        .fetch_optional(&state.db.pool)
+        .await?
+        .ok_or(ApiError::Unauthorized)?;
+        if current_role != user.role {
+            tracing::warn!(user_id = %user.id, claimed = %user.role, current = %current_role, "auth.role.stale_claim");
+            return Err(ApiError::Forbidden);
+        }
+        Ok(Self(user, PhantomData))
+    }
+}
@@ -0,0 +1,71 @@
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::request::Parts,
+};
+
+use crate::state::AppState;
+
+const MAX_USER_AGENT_LEN: usize = 512;
+const MAX_DEVICE_LABEL_LEN: usize = 100;
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Who is calling: peer IP, user agent and the optional `X-Device-Name` label sent by the apps.
+/// Recorded on sessions so users can recognise their devices.
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub ip: Option<IpAddr>,
+    pub user_agent: Option<String>,
+    pub device_label: Option<String>,
+    /// `X-Request-Id` of the call (set by the request id middleware), for the audit trail.
+    pub request_id: Option<String>,
+}
+
+impl FromRequestParts<AppState> for ClientInfo {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let forwarded_ip = if state.config.trust_proxy_headers {
+            parts
+                .headers
+                .get("x-forwarded-for")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.split(',').next())
+                .or_else(|| parts.headers.get("x-real-ip").and_then(|v| v.to_str().ok()))
+                .and_then(|v| v.trim().parse::<IpAddr>().ok())
+        } else {
+            None
+        };
+        let ip = forwarded_ip.or_else(|| {
+            parts
+                .extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip())
+        });
+
+        Ok(Self {
+            ip,
+            user_agent: header_text(parts, "user-agent", MAX_USER_AGENT_LEN),
+            device_label: header_text(parts, "x-device-name", MAX_DEVICE_LABEL_LEN),
+            request_id: header_text(parts, "x-request-id", MAX_REQUEST_ID_LEN),
+        })
+    }
+}
+
+impl ClientInfo {
+    pub fn ip_string(&self) -> Option<String> {
+        self.ip.map(|ip| ip.to_string())
+    }
+}
+
+fn header_text(parts: &Parts, name: &str, max_chars: usize) -> Option<String> {
+    parts
+        .headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| v.chars().take(max_chars).collect())
+}
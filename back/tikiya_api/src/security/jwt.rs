@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::config::AppConfig;
+use crate::error::ApiError;
+
+/// Access-token signing and verification keys.
+///
+/// With `JWT_SIGNING_KEY_FILE` set, tokens are signed with that Ed25519 (EdDSA) or RSA (RS256)
+/// private key and carry its `kid`. Keys listed in `JWT_VERIFY_KEY_FILES` stay accepted for
+/// verification so a rotation does not log everyone out; all of them are published as a JWKS.
+/// Without a signing key, tokens are HS256-signed with `JWT_SECRET` (no JWKS).
+pub struct JwtKeys {
+    signing: SigningKey,
+    verifying: HashMap<String, VerifyingKey>,
+    hs256: Option<DecodingKey>,
+    jwks: Value,
+}
+
+struct SigningKey {
+    kid: Option<String>,
+    alg: Algorithm,
+    key: EncodingKey,
+}
+
+struct VerifyingKey {
+    alg: Algorithm,
+    key: DecodingKey,
+}
+
+/// Public half of a configured key, in the shapes jsonwebtoken and JWKS need.
+struct PublicKey {
+    kid: String,
+    alg: Algorithm,
+    key: DecodingKey,
+    jwk: Value,
+}
+
+impl JwtKeys {
+    pub fn from_config(config: &AppConfig) -> anyhow::Result<Self> {
+        let hs256_decoding = DecodingKey::from_secret(config.jwt_secret.as_bytes());
+
+        let Some(path) = config.jwt_signing_key_file.as_deref() else {
+            return Ok(Self {
+                signing: SigningKey {
+                    kid: None,
+                    alg: Algorithm::HS256,
+                    key: EncodingKey::from_secret(config.jwt_secret.as_bytes()),
+                },
+                verifying: HashMap::new(),
+                hs256: Some(hs256_decoding),
+                jwks: json!({ "keys": [] }),
+            });
+        };
+
+        let pem = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("JWT_SIGNING_KEY_FILE {}: {}", path, e))?;
+        let active = public_key_from_pem(&pem)?;
+        let signing = SigningKey {
+            kid: Some(active.kid.clone()),
+            alg: active.alg,
+            key: match active.alg {
+                Algorithm::EdDSA => EncodingKey::from_ed_pem(pem.as_bytes())?,
+                _ => EncodingKey::from_rsa_pem(pem.as_bytes())?,
+            },
+        };
+
+        let mut public_keys = vec![active];
+        for path in &config.jwt_verify_key_files {
+            let pem = std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("JWT_VERIFY_KEY_FILES {}: {}", path, e))?;
+            public_keys.push(public_key_from_pem(&pem)?);
+        }
+
+        let jwks = json!({ "keys": public_keys.iter().map(|k| k.jwk.clone()).collect::<Vec<_>>() });
+        let verifying = public_keys
+            .into_iter()
+            .map(|k| (k.kid, VerifyingKey { alg: k.alg, key: k.key }))
+            .collect::<HashMap<_, _>>();
+
+        tracing::info!(
+            kid = ?signing.kid,
+            alg = ?signing.alg,
+            verification_keys = verifying.len(),
+            accept_hs256 = config.jwt_accept_hs256,
+            "jwt.keys.loaded"
+        );
+
+        Ok(Self {
+            signing,
+            verifying,
+            hs256: config.jwt_accept_hs256.then_some(hs256_decoding),
+            jwks,
+        })
+    }
+
+    pub fn sign<T: Serialize>(&self, claims: &T) -> Result<String, ApiError> {
+        let mut header = Header::new(self.signing.alg);
+        header.kid = self.signing.kid.clone();
+        encode(&header, claims, &self.signing.key).map_err(|err| {
+            tracing::error!(?err, "auth.token.encode_failed");
+            ApiError::Internal
+        })
+    }
+
+    /// Verifies signature, `exp`, `aud` and `iss`. The key is picked by `kid`; kid-less tokens are
+    /// only accepted as HS256 while that mode is enabled.
+    pub fn verify<T: DeserializeOwned>(&self, token: &str, audience: &str, issuer: &str) -> Result<T, ApiError> {
+        let header = decode_header(token).map_err(|_| ApiError::Unauthorized)?;
+        let (alg, key) = match header.kid.as_deref() {
+            Some(kid) => {
+                let key = self.verifying.get(kid).ok_or(ApiError::Unauthorized)?;
+                (key.alg, &key.key)
+            }
+            None => (Algorithm::HS256, self.hs256.as_ref().ok_or(ApiError::Unauthorized)?),
+        };
+        if header.alg != alg {
+            return Err(ApiError::Unauthorized);
+        }
+
+        let mut validation = Validation::new(alg);
+        validation.set_audience(&[audience]);
+        validation.set_issuer(&[issuer]);
+
+        decode::<T>(token, key, &validation)
+            .map(|data| data.claims)
+            .map_err(|_| ApiError::Unauthorized)
+    }
+
+    /// JSON Web Key Set of every accepted asymmetric key (`/.well-known/jwks.json`).
+    pub fn jwks(&self) -> &Value {
+        &self.jwks
+    }
+}
+
+fn public_key_from_pem(pem: &str) -> anyhow::Result<PublicKey> {
+    use ed25519_dalek::pkcs8::{DecodePrivateKey as _, DecodePublicKey as _};
+    use rsa::pkcs1::{DecodeRsaPrivateKey as _, DecodeRsaPublicKey as _};
+    use rsa::traits::PublicKeyParts as _;
+
+    let ed25519 = ed25519_dalek::SigningKey::from_pkcs8_pem(pem)
+        .map(|k| k.verifying_key())
+        .or_else(|_| ed25519_dalek::VerifyingKey::from_public_key_pem(pem));
+    if let Ok(public) = ed25519 {
+        let x = URL_SAFE_NO_PAD.encode(public.as_bytes());
+        let kid = key_id(public.as_bytes());
+        return Ok(PublicKey {
+            jwk: json!({ "kty": "OKP", "crv": "Ed25519", "x": x, "kid": kid, "alg": "EdDSA", "use": "sig" }),
+            key: DecodingKey::from_ed_components(&x)?,
+            alg: Algorithm::EdDSA,
+            kid,
+        });
+    }
+
+    let rsa = rsa::RsaPrivateKey::from_pkcs8_pem(pem)
+        .or_else(|_| rsa::RsaPrivateKey::from_pkcs1_pem(pem))
+        .map(|k| k.to_public_key())
+        .or_else(|_| rsa::RsaPublicKey::from_public_key_pem(pem))
+        .or_else(|_| rsa::RsaPublicKey::from_pkcs1_pem(pem));
+    if let Ok(public) = rsa {
+        let n_bytes = public.n().to_bytes_be();
+        let n = URL_SAFE_NO_PAD.encode(&n_bytes);
+        let e = URL_SAFE_NO_PAD.encode(public.e().to_bytes_be());
+        let kid = key_id(&n_bytes);
+        return Ok(PublicKey {
+            jwk: json!({ "kty": "RSA", "n": n, "e": e, "kid": kid, "alg": "RS256", "use": "sig" }),
+            key: DecodingKey::from_rsa_components(&n, &e)?,
+            alg: Algorithm::RS256,
+            kid,
+        });
+    }
+
+    anyhow::bail!("unsupported JWT key: expected an Ed25519 or RSA key in PEM format")
+}
+
+/// Stable key id derived from the public key material.
+fn key_id(public_bytes: &[u8]) -> String {
+    let digest = Sha256::digest(public_bytes);
+    URL_SAFE_NO_PAD.encode(&digest[..12])
+}
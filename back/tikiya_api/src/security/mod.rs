@@ -1 +1,11 @@
+pub mod auth_user;
+pub mod client_info;
+pub mod jwt;
 pub mod oauth_state;
+pub mod password;
+pub mod password_policy;
+pub mod phone;
+pub mod remote_jwks;
+pub mod secret_box;
+pub mod tokens;
+pub mod webauthn;
@@ -6,17 +6,17 @@ use argon2::password_hash::rand_core::{OsRng, RngCore};
 
 use crate::error::ApiError;
 
-const STATE_TTL_SECS: i64 = 10 * 60;
+pub const STATE_TTL_SECS: i64 = 10 * 60;
 
 #[derive(Debug, Serialize, Deserialize)]
 struct StatePayload {
     ts: i64,
     nonce: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    client_state: Option<String>,
 }
 
-pub fn mint_state(jwt_secret: &str, client_state: Option<&str>) -> Result<String, ApiError> {
+/// Signed `state` parameter. The returned nonce keys the server-side `oauth_states` row that
+/// makes it single-use.
+pub fn mint_state(secret: &str) -> Result<(String, String), ApiError> {
     let mut nonce_bytes = [0u8; 16];
     let mut rng = OsRng;
     rng.fill_bytes(&mut nonce_bytes);
@@ -24,28 +24,28 @@ pub fn mint_state(jwt_secret: &str, client_state: Option<&str>) -> Result<String
 
     let payload = StatePayload {
         ts: chrono::Utc::now().timestamp(),
-        nonce,
-        client_state: client_state.map(|s| s.to_string()),
+        nonce: nonce.clone(),
     };
 
     let payload_json = serde_json::to_vec(&payload).map_err(|_| ApiError::Internal)?;
     let payload_b64 = URL_SAFE_NO_PAD.encode(&payload_json);
 
-    let mut mac = Hmac::<Sha256>::new_from_slice(jwt_secret.as_bytes()).map_err(|_| ApiError::Internal)?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).map_err(|_| ApiError::Internal)?;
     mac.update(payload_b64.as_bytes());
     let sig = mac.finalize().into_bytes();
     let sig_b64 = URL_SAFE_NO_PAD.encode(sig);
 
-    Ok(format!("{}.{}", payload_b64, sig_b64))
+    Ok((format!("{}.{}", payload_b64, sig_b64), nonce))
 }
 
-pub fn verify_state(jwt_secret: &str, state: &str) -> Result<(), ApiError> {
+/// Checks signature and TTL, and returns the nonce to consume.
+pub fn verify_state(secret: &str, state: &str) -> Result<String, ApiError> {
     let (payload_b64, sig_b64) = state.split_once('.').ok_or(ApiError::Unauthorized)?;
     let sig = URL_SAFE_NO_PAD
         .decode(sig_b64)
         .map_err(|_| ApiError::Unauthorized)?;
 
-    let mut mac = Hmac::<Sha256>::new_from_slice(jwt_secret.as_bytes()).map_err(|_| ApiError::Internal)?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).map_err(|_| ApiError::Internal)?;
     mac.update(payload_b64.as_bytes());
     mac.verify_slice(&sig).map_err(|_| ApiError::Unauthorized)?;
 
@@ -59,5 +59,5 @@ pub fn verify_state(jwt_secret: &str, state: &str) -> Result<(), ApiError> {
         return Err(ApiError::Unauthorized);
     }
 
-    Ok(())
+    Ok(payload.nonce)
 }
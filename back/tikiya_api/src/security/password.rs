@@ -0,0 +1,87 @@
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, KeyId, Params, ParamsBuilder, Version};
+
+use crate::config::PasswordHashConfig;
+use crate::error::ApiError;
+
+/// `keyid` recorded in the PHC string of hashes computed with the pepper, so a hash says
+/// whether it needs the pepper to verify.
+const PEPPER_KEY_ID: &[u8] = b"pepper1";
+
+/// Argon2id hashing with the configured cost parameters and optional pepper. Blocking: call from
+/// `spawn_blocking`.
+pub struct PasswordHashing<'a> {
+    config: &'a PasswordHashConfig,
+}
+
+impl<'a> PasswordHashing<'a> {
+    pub fn new(config: &'a PasswordHashConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn hash(&self, password: &str) -> Result<String, ApiError> {
+        let salt = SaltString::generate(&mut OsRng);
+        let params = self.params()?;
+        let hash = match self.config.pepper.as_deref() {
+            Some(pepper) => Argon2::new_with_secret(pepper.as_bytes(), Algorithm::Argon2id, Version::V0x13, params)?
+                .hash_password(password.as_bytes(), &salt)?
+                .to_string(),
+            None => Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                .hash_password(password.as_bytes(), &salt)?
+                .to_string(),
+        };
+        Ok(hash)
+    }
+
+    /// Checks `password` against a stored hash, whatever parameters it was computed with.
+    /// Malformed hashes, and peppered hashes while no pepper is configured, count as a mismatch.
+    pub fn verify(&self, hash: &str, password: &str) -> bool {
+        let Ok(parsed) = PasswordHash::new(hash) else {
+            return false;
+        };
+        // Cost parameters come from the hash itself; only the secret has to be supplied.
+        if !is_peppered(&parsed) {
+            return Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok();
+        }
+        let Some(pepper) = self.config.pepper.as_deref() else {
+            tracing::error!("auth.password.peppered_hash_without_pepper");
+            return false;
+        };
+        Argon2::new_with_secret(pepper.as_bytes(), Algorithm::Argon2id, Version::V0x13, Params::default())
+            .map(|argon2| argon2.verify_password(password.as_bytes(), &parsed).is_ok())
+            .unwrap_or(false)
+    }
+
+    /// Whether a hash that just verified should be replaced: other algorithm or cost
+    /// parameters, or pepper added / removed since it was computed.
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        let Ok(parsed) = PasswordHash::new(hash) else {
+            return true;
+        };
+        let Ok(params) = Params::try_from(&parsed) else {
+            return true;
+        };
+        parsed.algorithm != Algorithm::Argon2id.ident()
+            || parsed.version != Some(Version::V0x13.into())
+            || params.m_cost() != self.config.memory_kib
+            || params.t_cost() != self.config.iterations
+            || params.p_cost() != self.config.parallelism
+            || is_peppered(&parsed) != self.config.pepper.is_some()
+    }
+
+    fn params(&self) -> Result<Params, ApiError> {
+        let mut builder = ParamsBuilder::new();
+        builder
+            .m_cost(self.config.memory_kib)
+            .t_cost(self.config.iterations)
+            .p_cost(self.config.parallelism);
+        if self.config.pepper.is_some() {
+            builder.keyid(KeyId::new(PEPPER_KEY_ID)?);
+        }
+        Ok(builder.build()?)
+    }
+}
+
+fn is_peppered(hash: &PasswordHash<'_>) -> bool {
+    Params::try_from(hash).is_ok_and(|params| params.keyid() == PEPPER_KEY_ID)
+}
@@ -0,0 +1,408 @@
+//! Password policy: minimum length, a zxcvbn-style strength estimate, no email inside the
+//! password, and an offline breached-password list (SHA-1 hashes, Have I Been Pwned format).
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use sha1::{Digest, Sha1};
+
+use crate::config::PasswordPolicyConfig;
+use crate::error::{ApiError, FieldError};
+
+/// Most common passwords and words seen in leaks, most frequent first (the rank sets the cost).
+const COMMON_WORDS: &[&str] = &[
+    "123456", "password", "123456789", "12345678", "12345", "qwerty", "azerty", "111111", "123123", "motdepasse",
+    "abc123", "iloveyou", "000000", "1234567", "dragon", "monkey", "letmein", "football", "soleil", "bonjour",
+    "admin", "welcome", "master", "sunshine", "princess", "shadow", "superman", "batman", "loveme", "trustno1",
+    "baseball", "freedom", "whatever", "hello", "summer", "winter", "secret", "doudou", "chouchou", "marseille",
+    "algerie", "algeria", "alger", "dzair", "oran", "constantine", "allah", "bismillah", "mohamed", "amine",
+    "yacine", "karim", "sofiane", "ahmed", "fatima", "amira", "nour", "samir", "nassim", "walid",
+    "tikiya", "ticket", "event", "love", "pass", "user", "login", "qwertz", "michael", "jordan",
+    "nicolas", "thomas", "camille", "julien", "pokemon", "naruto", "barcelona", "realmadrid", "mouloudia", "chabab",
+];
+
+/// Keyboard rows (QWERTY, AZERTY, digits) for adjacent-key runs like "qwerty" or "azsx".
+const KEYBOARD_ROWS: &[&str] = &["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm", "azertyuiop", "qsdfghjklm", "wxcvbn"];
+
+/// Longest line accepted in the breached list: 40 hex digits, `:count` and a CRLF.
+const MAX_BREACHED_LINE: usize = 64;
+/// Lines checked for sort order when the breached list is opened.
+const SORT_CHECK_LINES: usize = 1000;
+
+pub struct PasswordPolicy {
+    min_length: usize,
+    min_score: u8,
+    breached: Option<BreachedList>,
+}
+
+impl PasswordPolicy {
+    pub fn from_config(config: &PasswordPolicyConfig) -> Result<Self, std::io::Error> {
+        let breached = config
+            .breached_list_path
+            .as_deref()
+            .map(|path| BreachedList::open(Path::new(path)))
+            .transpose()?;
+        tracing::info!(
+            breached_list_bytes = breached.as_ref().map_or(0, |list| list.len),
+            "password_policy.loaded"
+        );
+        Ok(Self {
+            min_length: config.min_length,
+            min_score: config.min_score,
+            breached,
+        })
+    }
+
+    /// Rejects `password` with one field error per failed rule. `user_inputs` (names, phone...)
+    /// are treated as guessable words by the strength estimate.
+    pub fn check(&self, field: &str, password: &str, email: Option<&str>, user_inputs: &[&str]) -> Result<(), ApiError> {
+        let mut errors = Vec::new();
+
+        if password.chars().count() < self.min_length {
+            errors.push(FieldError::new(
+                field,
+                "password_too_short",
+                format!("must be at least {} characters", self.min_length),
+            ));
+        }
+
+        if let Some(email) = email.map(str::to_lowercase) {
+            let lower = password.to_lowercase();
+            let local = email.split('@').next().unwrap_or_default();
+            if lower.contains(&email) || (local.chars().count() >= 3 && lower.contains(local)) {
+                errors.push(FieldError::new(field, "password_contains_email", "must not contain your email address"));
+            }
+        }
+
+        if self.is_breached(password) {
+            errors.push(FieldError::new(
+                field,
+                "password_breached",
+                "appears in a known data breach, choose another one",
+            ));
+        }
+
+        let mut inputs: Vec<&str> = user_inputs.to_vec();
+        if let Some(email) = email {
+            inputs.push(email);
+        }
+        let score = strength_score(password, &inputs);
+        if score < self.min_score {
+            errors.push(FieldError::new(
+                field,
+                "password_too_weak",
+                format!("is too easy to guess (strength {score}/4, at least {} required)", self.min_score),
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiError::InvalidFields(errors))
+        }
+    }
+
+    fn is_breached(&self, password: &str) -> bool {
+        let Some(list) = &self.breached else {
+            return false;
+        };
+        let digest: [u8; 20] = Sha1::digest(password.as_bytes()).into();
+        // A broken list must not block sign-ups: report it and let the password through.
+        list.contains(&digest).unwrap_or_else(|err| {
+            tracing::error!(error = %err, "password_policy.breached_list_unreadable");
+            false
+        })
+    }
+}
+
+/// Breached-password list searched on disk, so lists the size of Have I Been Pwned's (tens of
+/// gigabytes) cost no memory. The file holds one SHA-1 hex digest per line, optionally followed
+/// by `:count`, sorted by hash (the "ordered by hash" download); a lookup is a binary search over
+/// byte offsets, about 35 small reads for the full list.
+struct BreachedList {
+    path: PathBuf,
+    len: u64,
+}
+
+impl BreachedList {
+    /// Opens the list and checks that its first lines are valid and in ascending order.
+    fn open(path: &Path) -> Result<Self, std::io::Error> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        let list = Self {
+            path: path.to_path_buf(),
+            len,
+        };
+
+        let mut offset = 0;
+        let mut previous: Option<[u8; 20]> = None;
+        for _ in 0..SORT_CHECK_LINES {
+            if offset >= len {
+                break;
+            }
+            let (digest, next) = list.line_at(&mut file, offset)?;
+            if previous.is_some_and(|previous| previous > digest) {
+                return Err(invalid_list("PASSWORD_BREACHED_LIST must be sorted by hash"));
+            }
+            previous = Some(digest);
+            offset = next;
+        }
+        Ok(list)
+    }
+
+    fn contains(&self, digest: &[u8; 20]) -> Result<bool, std::io::Error> {
+        let mut file = File::open(&self.path)?;
+        // `lo` is always the start of a line; lines starting in [lo, hi) are still candidates.
+        let (mut lo, mut hi) = (0, self.len);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let mut start = if mid == lo { lo } else { self.next_line_start(&mut file, mid)? };
+            if start >= hi {
+                start = lo;
+            }
+            let (line_digest, next) = self.line_at(&mut file, start)?;
+            match digest.cmp(&line_digest) {
+                std::cmp::Ordering::Equal => return Ok(true),
+                std::cmp::Ordering::Less => hi = start,
+                std::cmp::Ordering::Greater => lo = next,
+            }
+        }
+        Ok(false)
+    }
+
+    /// Offset of the first line starting after `offset - 1`, i.e. at or after `offset`.
+    fn next_line_start(&self, file: &mut File, offset: u64) -> Result<u64, std::io::Error> {
+        let buf = read_chunk(file, offset - 1)?;
+        match buf.iter().position(|b| *b == b'\n') {
+            Some(newline) => Ok(offset + newline as u64),
+            None if buf.len() < MAX_BREACHED_LINE => Ok(self.len),
+            None => Err(invalid_list("line too long in PASSWORD_BREACHED_LIST")),
+        }
+    }
+
+    /// Digest on the line starting at `offset`, and the offset of the next line.
+    fn line_at(&self, file: &mut File, offset: u64) -> Result<([u8; 20], u64), std::io::Error> {
+        let buf = read_chunk(file, offset)?;
+        let end = buf.iter().position(|b| *b == b'\n').unwrap_or(buf.len());
+        if end == MAX_BREACHED_LINE {
+            return Err(invalid_list("line too long in PASSWORD_BREACHED_LIST"));
+        }
+        let line = std::str::from_utf8(&buf[..end]).unwrap_or_default();
+        let digest = line
+            .split(':')
+            .next()
+            .and_then(|hex| decode_sha1_hex(hex.trim()))
+            .ok_or_else(|| invalid_list("invalid line in PASSWORD_BREACHED_LIST"))?;
+        Ok((digest, offset + end as u64 + 1))
+    }
+}
+
+fn read_chunk(file: &mut File, offset: u64) -> Result<Vec<u8>, std::io::Error> {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut buf = Vec::with_capacity(MAX_BREACHED_LINE);
+    file.take(MAX_BREACHED_LINE as u64).read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+fn invalid_list(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn decode_sha1_hex(hex: &str) -> Option<[u8; 20]> {
+    if hex.len() != 40 {
+        return None;
+    }
+    let mut out = [0u8; 20];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+    Some(out)
+}
+
+/// Strength on zxcvbn's 0–4 scale, from an estimate of the guesses needed: below 10^3, 10^6,
+/// 10^8 and 10^10 guesses give 0 to 3, anything above 4. Dictionary words (common passwords and
+/// `user_inputs`, also in l33t spelling), years, repeats, sequences and keyboard runs are cheap;
+/// other characters cost their character class.
+pub fn strength_score(password: &str, user_inputs: &[&str]) -> u8 {
+    let chars: Vec<char> = password.chars().collect();
+    let lower: Vec<char> = chars.iter().map(|c| c.to_ascii_lowercase()).collect();
+    let unleeted: Vec<char> = lower.iter().map(|c| unleet(*c)).collect();
+    let mut covered = vec![false; chars.len()];
+    // log10 of the estimated number of guesses.
+    let mut guesses_log10 = 0.0_f64;
+
+    let user_words: Vec<String> = user_inputs
+        .iter()
+        .flat_map(|input| input.split(|c: char| !c.is_alphanumeric()))
+        .filter(|word| word.chars().count() >= 3)
+        .map(|word| word.to_lowercase())
+        .collect();
+    let dictionary = COMMON_WORDS
+        .iter()
+        .enumerate()
+        .map(|(rank, word)| (word.to_string(), rank + 2))
+        .chain(user_words.into_iter().map(|word| (word, 1)));
+
+    for (word, rank) in dictionary {
+        let word: Vec<char> = word.chars().collect();
+        if word.len() > lower.len() {
+            continue;
+        }
+        for start in 0..=lower.len() - word.len() {
+            let range = start..start + word.len();
+            let plain = lower[range.clone()] == word[..];
+            if (plain || unleeted[range.clone()] == word[..]) && !covered[range.clone()].iter().any(|c| *c) {
+                covered[range.clone()].iter_mut().for_each(|c| *c = true);
+                let capitalized = chars[range].iter().any(|c| c.is_uppercase());
+                let leet = !plain;
+                guesses_log10 += (rank as f64).log10() + if capitalized { 0.3 } else { 0.0 } + if leet { 0.3 } else { 0.0 };
+            }
+        }
+    }
+
+    // Years (1900–2099) are one guess out of ~200.
+    for start in 0..chars.len().saturating_sub(3) {
+        let window: String = chars[start..start + 4].iter().collect();
+        let is_year = (window.starts_with("19") || window.starts_with("20")) && window.chars().all(|c| c.is_ascii_digit());
+        if is_year && !covered[start..start + 4].iter().any(|c| *c) {
+            covered[start..start + 4].iter_mut().for_each(|c| *c = true);
+            guesses_log10 += 200f64.log10();
+        }
+    }
+
+    let mut previous: Option<char> = None;
+    for (i, c) in lower.iter().enumerate() {
+        if covered[i] {
+            previous = None;
+            continue;
+        }
+        let cheap = previous.is_some_and(|p| p == *c || (p as i32 - *c as i32).abs() == 1 || keyboard_adjacent(p, *c));
+        guesses_log10 += if cheap { 2f64.log10() } else { (class_size(chars[i]) as f64).log10() };
+        previous = Some(*c);
+    }
+
+    match guesses_log10 {
+        g if g < 3.0 => 0,
+        g if g < 6.0 => 1,
+        g if g < 8.0 => 2,
+        g if g < 10.0 => 3,
+        _ => 4,
+    }
+}
+
+fn unleet(c: char) -> char {
+    match c {
+        '0' => 'o',
+        '1' | '!' => 'i',
+        '3' => 'e',
+        '4' | '@' => 'a',
+        '5' | '$' => 's',
+        '7' => 't',
+        other => other,
+    }
+}
+
+fn keyboard_adjacent(a: char, b: char) -> bool {
+    KEYBOARD_ROWS.iter().any(|row| {
+        let row: Vec<char> = row.chars().collect();
+        row.windows(2).any(|pair| (pair[0] == a && pair[1] == b) || (pair[0] == b && pair[1] == a))
+    })
+}
+
+fn class_size(c: char) -> u32 {
+    match c {
+        '0'..='9' => 10,
+        'a'..='z' | 'A'..='Z' => 26,
+        c if c.is_ascii() => 33,
+        _ => 100,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sha1_hex(password: &str) -> String {
+        Sha1::digest(password.as_bytes()).iter().map(|b| format!("{b:02X}")).collect()
+    }
+
+    fn write_list(lines: &[String]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("tikiya-breached-{}.txt", uuid::Uuid::new_v4()));
+        std::fs::write(&path, lines.join("\r\n")).unwrap();
+        path
+    }
+
+    #[test]
+    fn finds_every_listed_hash_on_disk() {
+        let mut hashes: Vec<String> = (0..500).map(|i| sha1_hex(&format!("leaked-{i}"))).collect();
+        hashes.sort();
+        let lines: Vec<String> = hashes.iter().enumerate().map(|(i, h)| format!("{h}:{}", i * 37)).collect();
+        let path = write_list(&lines);
+        let list = BreachedList::open(&path).unwrap();
+
+        for i in 0..500 {
+            let digest: [u8; 20] = Sha1::digest(format!("leaked-{i}").as_bytes()).into();
+            assert!(list.contains(&digest).unwrap(), "leaked-{i}");
+        }
+        for i in 0..500 {
+            let digest: [u8; 20] = Sha1::digest(format!("fresh-{i}").as_bytes()).into();
+            assert!(!list.contains(&digest).unwrap(), "fresh-{i}");
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn rejects_unsorted_or_malformed_lists() {
+        let mut hashes = vec![sha1_hex("a"), sha1_hex("b"), sha1_hex("c")];
+        hashes.sort();
+        hashes.swap(0, 2);
+        let unsorted = write_list(&hashes);
+        assert!(BreachedList::open(&unsorted).is_err());
+
+        let malformed = write_list(&["not a hash".to_string()]);
+        assert!(BreachedList::open(&malformed).is_err());
+
+        let empty = write_list(&[]);
+        let list = BreachedList::open(&empty).unwrap();
+        assert!(!list.contains(&[0u8; 20]).unwrap());
+
+        for path in [unsorted, malformed, empty] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn scores_follow_guess_thresholds() {
+        // Each unrelated lowercase letter is log10(26) ≈ 1.41: 4 → 10^5.7, 5 → 10^7.1,
+        // 6 → 10^8.5, 8 → 10^11.3 guesses.
+        assert_eq!(strength_score("", &[]), 0);
+        assert_eq!(strength_score("xkqz", &[]), 1);
+        assert_eq!(strength_score("xkqzm", &[]), 2);
+        assert_eq!(strength_score("xkqzwm", &[]), 3);
+        assert_eq!(strength_score("xkqzwmvj", &[]), 4);
+        // Common passwords and sequences stay at 0 whatever their length.
+        assert_eq!(strength_score("password", &[]), 0);
+        assert_eq!(strength_score("abcdef", &[]), 0);
+    }
+
+    #[test]
+    fn leet_spelling_is_a_dictionary_word() {
+        assert_eq!(strength_score("p4$$w0rd", &[]), strength_score("password", &[]));
+        assert!(strength_score("m0nk3y", &[]) <= 1);
+    }
+
+    #[test]
+    fn user_inputs_and_years_are_cheap() {
+        assert!(strength_score("benali1987", &["Karim Benali"]) < strength_score("benali1987", &[]));
+        assert!(strength_score("xkqz1987", &[]) < strength_score("xkqz1a8b", &[]));
+        assert!(strength_score("xkqz2024", &[]) < strength_score("xkqz2a2b", &[]));
+    }
+
+    #[test]
+    fn keyboard_runs_and_repeats_are_cheap() {
+        assert!(strength_score("asdfghjk", &[]) < strength_score("agjdkslh", &[]));
+        assert!(strength_score("zzzzzzzz", &[]) < strength_score("zqxjvkwm", &[]));
+        assert!(strength_score("qsdfghjk", &[]) <= 1);
+    }
+}
@@ -0,0 +1,28 @@
+use crate::error::ApiError;
+
+/// Normalizes a phone number to E.164 (`+213555123456`). Accepts international numbers (`+` or
+/// `00` prefix) and national ones starting with `0`, which get `default_country_code`. Spaces,
+/// dots, dashes and parentheses are ignored.
+pub fn normalize_e164(input: &str, default_country_code: &str) -> Result<String, ApiError> {
+    let invalid = || ApiError::Validation("phone: expected an international (+213...) or national (0...) number".into());
+    let compact: String = input
+        .chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, '-' | '.' | '(' | ')'))
+        .collect();
+
+    let digits = if let Some(rest) = compact.strip_prefix('+') {
+        rest.to_string()
+    } else if let Some(rest) = compact.strip_prefix("00") {
+        rest.to_string()
+    } else if let Some(rest) = compact.strip_prefix('0') {
+        format!("{default_country_code}{rest}")
+    } else {
+        return Err(invalid());
+    };
+
+    // E.164: country code + subscriber number, at most 15 digits, no leading zero.
+    if !(8..=15).contains(&digits.len()) || !digits.chars().all(|c| c.is_ascii_digit()) || digits.starts_with('0') {
+        return Err(invalid());
+    }
+    Ok(format!("+{digits}"))
+}
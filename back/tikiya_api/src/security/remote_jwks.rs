@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use jsonwebtoken::jwk::{AlgorithmParameters, Jwk};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::de::DeserializeOwned;
+use tokio::sync::{Mutex, RwLock};
+
+use crate::error::ApiError;
+
+/// Used when the key server sends no usable `Cache-Control: max-age`.
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+/// An unknown `kid` triggers a refetch at most this often (keys rotated before our cache expired).
+const MIN_REFETCH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Signing keys of an external issuer (Google, OIDC providers), fetched from its JWKS URL and
+/// cached for as long as the response's `Cache-Control: max-age` allows.
+pub struct RemoteJwks {
+    url: String,
+    http: reqwest::Client,
+    cache: RwLock<CachedKeys>,
+    /// Serialises refetches so concurrent misses hit the key server once.
+    refresh: Mutex<()>,
+}
+
+#[derive(Default)]
+struct CachedKeys {
+    keys: HashMap<String, (Algorithm, DecodingKey)>,
+    fetched_at: Option<Instant>,
+    max_age: Duration,
+}
+
+impl CachedKeys {
+    fn fresh(&self) -> bool {
+        self.fetched_at.is_some_and(|at| at.elapsed() < self.max_age)
+    }
+}
+
+impl RemoteJwks {
+    pub fn new(url: impl Into<String>, http: reqwest::Client) -> Self {
+        Self {
+            url: url.into(),
+            http,
+            cache: RwLock::new(CachedKeys::default()),
+            refresh: Mutex::new(()),
+        }
+    }
+
+    /// Verifies the signature against the key named by the token's `kid`, then `exp`, `aud` and
+    /// `iss`. An empty `issuers` leaves the issuer check to the caller (templated issuers).
+    pub async fn verify<T: DeserializeOwned>(&self, token: &str, audiences: &[&str], issuers: &[&str]) -> Result<T, ApiError> {
+        let header = decode_header(token).map_err(|_| ApiError::Unauthorized)?;
+        let kid = header.kid.ok_or(ApiError::Unauthorized)?;
+        let (alg, key) = self.key(&kid).await?;
+        if header.alg != alg {
+            return Err(ApiError::Unauthorized);
+        }
+
+        let mut validation = Validation::new(alg);
+        validation.set_audience(audiences);
+        if !issuers.is_empty() {
+            validation.set_issuer(issuers);
+        }
+        validation.set_required_spec_claims(&["exp", "aud", "iss", "sub"]);
+
+        decode::<T>(token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(|err| {
+                tracing::warn!(url = %self.url, error = %err, "jwks.token_rejected");
+                ApiError::Unauthorized
+            })
+    }
+
+    async fn key(&self, kid: &str) -> Result<(Algorithm, DecodingKey), ApiError> {
+        {
+            let cache = self.cache.read().await;
+            if cache.fresh() {
+                if let Some(key) = cache.keys.get(kid) {
+                    return Ok(key.clone());
+                }
+            }
+        }
+
+        let _guard = self.refresh.lock().await;
+        // Another request may have refreshed while we waited.
+        {
+            let cache = self.cache.read().await;
+            if cache.fresh() {
+                if let Some(key) = cache.keys.get(kid) {
+                    return Ok(key.clone());
+                }
+            }
+            if cache.fetched_at.is_some_and(|at| at.elapsed() < MIN_REFETCH_INTERVAL) {
+                return Err(ApiError::Unauthorized);
+            }
+        }
+
+        let fetched = self.fetch().await?;
+        let mut cache = self.cache.write().await;
+        *cache = fetched;
+        cache.keys.get(kid).cloned().ok_or(ApiError::Unauthorized)
+    }
+
+    async fn fetch(&self) -> Result<CachedKeys, ApiError> {
+        let res = self.http.get(&self.url).send().await.map_err(|e| {
+            tracing::error!(url = %self.url, error = ?e, "jwks.request_failed");
+            ApiError::ServiceUnavailable
+        })?;
+        if !res.status().is_success() {
+            tracing::error!(url = %self.url, status = %res.status(), "jwks.fetch_failed");
+            return Err(ApiError::ServiceUnavailable);
+        }
+
+        let max_age = res
+            .headers()
+            .get(reqwest::header::CACHE_CONTROL)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_max_age)
+            .unwrap_or(DEFAULT_MAX_AGE);
+
+        #[derive(serde::Deserialize)]
+        struct RawJwkSet {
+            keys: Vec<serde_json::Value>,
+        }
+        let set: RawJwkSet = res.json().await.map_err(|e| {
+            tracing::error!(url = %self.url, error = ?e, "jwks.parse_failed");
+            ApiError::ServiceUnavailable
+        })?;
+
+        // Skip keys we cannot use (encryption keys, unknown types) instead of failing the set.
+        let keys = set
+            .keys
+            .into_iter()
+            .filter_map(|value| serde_json::from_value::<Jwk>(value).ok())
+            .filter_map(|jwk| {
+                let kid = jwk.common.key_id.clone()?;
+                let alg = key_algorithm(&jwk)?;
+                let key = DecodingKey::from_jwk(&jwk).ok()?;
+                Some((kid, (alg, key)))
+            })
+            .collect::<HashMap<_, _>>();
+
+        tracing::info!(url = %self.url, keys = keys.len(), max_age_secs = max_age.as_secs(), "jwks.refreshed");
+
+        Ok(CachedKeys {
+            keys,
+            fetched_at: Some(Instant::now()),
+            max_age,
+        })
+    }
+}
+
+/// Signature algorithm of a JWK: its `alg`, or the usual one for its key type.
+fn key_algorithm(jwk: &Jwk) -> Option<Algorithm> {
+    if let Some(alg) = jwk.common.key_algorithm {
+        return Algorithm::from_str(&alg.to_string()).ok();
+    }
+    match jwk.algorithm {
+        AlgorithmParameters::RSA(_) => Some(Algorithm::RS256),
+        AlgorithmParameters::EllipticCurve(_) => Some(Algorithm::ES256),
+        AlgorithmParameters::OctetKeyPair(_) => Some(Algorithm::EdDSA),
+        AlgorithmParameters::OctetKey(_) => None,
+    }
+}
+
+fn parse_max_age(cache_control: &str) -> Option<Duration> {
+    cache_control
+        .split(',')
+        .filter_map(|directive| directive.trim().strip_prefix("max-age="))
+        .find_map(|secs| secs.trim().parse::<u64>().ok())
+        .map(|secs| Duration::from_secs(secs).max(MIN_REFETCH_INTERVAL))
+}
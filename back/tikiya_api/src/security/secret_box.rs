@@ -0,0 +1,98 @@
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+use crate::error::ApiError;
+
+const SEALED_PREFIX: &str = "v1:";
+const NONCE_BYTES: usize = 12;
+
+/// AES-256-GCM for small secrets stored in the database (TOTP seeds). Sealed values read
+/// `v1:<base64url(nonce || ciphertext)>`; the associated data (the owner's id) ties a value to
+/// its row, so it cannot be copied onto another account.
+pub struct SecretBox {
+    cipher: Aes256Gcm,
+}
+
+impl SecretBox {
+    /// `key_material` is `MFA_ENCRYPTION_KEY` (or the key derived for it); the AES key is
+    /// derived from it with HKDF-SHA256 so any length of at least 32 characters works.
+    pub fn new(key_material: &str) -> Self {
+        let mut key = [0u8; 32];
+        hkdf::Hkdf::<sha2::Sha256>::new(None, key_material.as_bytes())
+            .expand(b"tikiya/secret-box/aes-256-gcm", &mut key)
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        Self {
+            cipher: Aes256Gcm::new(&key.into()),
+        }
+    }
+
+    pub fn seal(&self, plaintext: &str, associated_data: &[u8]) -> Result<String, ApiError> {
+        let mut nonce = [0u8; NONCE_BYTES];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext.as_bytes(), aad: associated_data })
+            .map_err(|_| ApiError::Internal)?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(format!("{SEALED_PREFIX}{}", URL_SAFE_NO_PAD.encode(sealed)))
+    }
+
+    /// Decrypts a value from [`seal`](Self::seal). A tampered value, another row's value or a
+    /// wrong key is an internal error: the stored secret is unusable.
+    pub fn open(&self, sealed: &str, associated_data: &[u8]) -> Result<String, ApiError> {
+        let bytes = sealed
+            .strip_prefix(SEALED_PREFIX)
+            .and_then(|b64| URL_SAFE_NO_PAD.decode(b64).ok())
+            .filter(|bytes| bytes.len() > NONCE_BYTES)
+            .ok_or(ApiError::Internal)?;
+        let (nonce, ciphertext) = bytes.split_at(NONCE_BYTES);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: associated_data })
+            .map_err(|_| {
+                tracing::error!("secret_box.decrypt_failed");
+                ApiError::Internal
+            })?;
+        String::from_utf8(plaintext).map_err(|_| ApiError::Internal)
+    }
+
+    pub fn is_sealed(value: &str) -> bool {
+        value.starts_with(SEALED_PREFIX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn round_trips_and_is_randomized() {
+        let secret_box = SecretBox::new(KEY);
+        let a = secret_box.seal("JBSWY3DPEHPK3PXP", b"user-1").unwrap();
+        let b = secret_box.seal("JBSWY3DPEHPK3PXP", b"user-1").unwrap();
+        assert!(SecretBox::is_sealed(&a));
+        assert!(!a.contains("JBSWY3DPEHPK3PXP"));
+        assert_ne!(a, b);
+        assert_eq!(secret_box.open(&a, b"user-1").unwrap(), "JBSWY3DPEHPK3PXP");
+    }
+
+    #[test]
+    fn rejects_other_owner_key_or_tampering() {
+        let secret_box = SecretBox::new(KEY);
+        let sealed = secret_box.seal("JBSWY3DPEHPK3PXP", b"user-1").unwrap();
+        assert!(secret_box.open(&sealed, b"user-2").is_err());
+        assert!(SecretBox::new("another key of at least 32 characters").open(&sealed, b"user-1").is_err());
+
+        let mut bytes = URL_SAFE_NO_PAD.decode(&sealed[SEALED_PREFIX.len()..]).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        let tampered = format!("{SEALED_PREFIX}{}", URL_SAFE_NO_PAD.encode(bytes));
+        assert!(secret_box.open(&tampered, b"user-1").is_err());
+        assert!(secret_box.open("JBSWY3DPEHPK3PXP", b"user-1").is_err());
+    }
+}
@@ -0,0 +1,22 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::error::ApiError;
+
+/// URL-safe random token with `bytes` bytes of entropy.
+pub fn random_token(bytes: usize) -> String {
+    let mut buf = vec![0u8; bytes];
+    OsRng.fill_bytes(&mut buf);
+    URL_SAFE_NO_PAD.encode(buf)
+}
+
+/// Keyed hash used to store high-entropy tokens at rest (`hmac:<b64>`).
+/// Deterministic, so the hash can be used directly as a lookup key.
+pub fn hmac_token(key: &str, token: &str) -> Result<String, ApiError> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes()).map_err(|_| ApiError::Internal)?;
+    mac.update(token.as_bytes());
+    let tag = mac.finalize().into_bytes();
+    Ok(format!("hmac:{}", URL_SAFE_NO_PAD.encode(tag)))
+}
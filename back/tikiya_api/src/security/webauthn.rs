@@ -0,0 +1,392 @@
+//! Minimal WebAuthn verification: authenticator data, "none"-style attestation objects (the
+//! attestation statement is not checked) and COSE public keys for ES256, EdDSA and RS256.
+
+use std::io::Cursor;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ciborium::Value;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::error::ApiError;
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL: u8 = 0x40;
+
+const COSE_ALG_ES256: i128 = -7;
+const COSE_ALG_EDDSA: i128 = -8;
+const COSE_ALG_RS256: i128 = -257;
+
+/// COSE algorithms offered in `pubKeyCredParams`, in order of preference.
+pub const SUPPORTED_ALGORITHMS: [i64; 3] = [COSE_ALG_ES256 as i64, COSE_ALG_EDDSA as i64, COSE_ALG_RS256 as i64];
+
+#[derive(Debug, Deserialize)]
+pub struct ClientData {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub challenge: String,
+    pub origin: String,
+}
+
+pub struct AuthenticatorData<'a> {
+    rp_id_hash: &'a [u8],
+    flags: u8,
+    pub sign_count: u32,
+    pub attested: Option<AttestedCredential>,
+}
+
+pub struct AttestedCredential {
+    pub credential_id: Vec<u8>,
+    /// COSE_Key, CBOR-encoded.
+    pub public_key: Vec<u8>,
+}
+
+impl AuthenticatorData<'_> {
+    pub fn rp_id_matches(&self, rp_id: &str) -> bool {
+        self.rp_id_hash == Sha256::digest(rp_id.as_bytes()).as_slice()
+    }
+
+    pub fn user_present(&self) -> bool {
+        self.flags & FLAG_USER_PRESENT != 0
+    }
+
+    pub fn user_verified(&self) -> bool {
+        self.flags & FLAG_USER_VERIFIED != 0
+    }
+}
+
+/// Decodes base64url, tolerating padding.
+pub fn decode_b64url(value: &str) -> Result<Vec<u8>, ApiError> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .map_err(|_| ApiError::Validation("invalid base64url".into()))
+}
+
+pub fn parse_client_data(raw: &[u8]) -> Result<ClientData, ApiError> {
+    serde_json::from_slice(raw).map_err(|_| ApiError::Validation("invalid clientDataJSON".into()))
+}
+
+pub fn parse_authenticator_data(data: &[u8]) -> Result<AuthenticatorData<'_>, ApiError> {
+    let invalid = || ApiError::Validation("invalid authenticator data".into());
+    if data.len() < 37 {
+        return Err(invalid());
+    }
+    let flags = data[32];
+    let sign_count = u32::from_be_bytes([data[33], data[34], data[35], data[36]]);
+
+    let attested = if flags & FLAG_ATTESTED_CREDENTIAL != 0 {
+        // aaguid (16) | credentialIdLength (2) | credentialId | credentialPublicKey (CBOR)
+        let rest = data.get(37 + 16..).ok_or_else(invalid)?;
+        let len = u16::from_be_bytes([*rest.first().ok_or_else(invalid)?, *rest.get(1).ok_or_else(invalid)?]) as usize;
+        let credential_id = rest.get(2..2 + len).ok_or_else(invalid)?.to_vec();
+        let key_bytes = &rest[2 + len..];
+        let mut cursor = Cursor::new(key_bytes);
+        let _: Value = ciborium::from_reader(&mut cursor).map_err(|_| invalid())?;
+        let public_key = key_bytes[..cursor.position() as usize].to_vec();
+        Some(AttestedCredential { credential_id, public_key })
+    } else {
+        None
+    };
+
+    Ok(AuthenticatorData {
+        rp_id_hash: &data[..32],
+        flags,
+        sign_count,
+        attested,
+    })
+}
+
+/// Extracts `authData` from a CBOR attestation object.
+pub fn attestation_auth_data(attestation_object: &[u8]) -> Result<Vec<u8>, ApiError> {
+    let invalid = || ApiError::Validation("invalid attestation object".into());
+    let value: Value = ciborium::from_reader(attestation_object).map_err(|_| invalid())?;
+    let map = value.into_map().map_err(|_| invalid())?;
+    map.into_iter()
+        .find(|(k, _)| k.as_text() == Some("authData"))
+        .and_then(|(_, v)| v.into_bytes().ok())
+        .ok_or_else(invalid)
+}
+
+pub enum CosePublicKey {
+    Es256(p256::ecdsa::VerifyingKey),
+    EdDsa(ed25519_dalek::VerifyingKey),
+    Rs256(rsa::pkcs1v15::VerifyingKey<Sha256>),
+}
+
+impl CosePublicKey {
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, ApiError> {
+        let invalid = || ApiError::Validation("unsupported credential public key".into());
+        let value: Value = ciborium::from_reader(bytes).map_err(|_| invalid())?;
+        let map = value.into_map().map_err(|_| invalid())?;
+        let field = |label: i128| {
+            map.iter()
+                .find(|(k, _)| k.as_integer().map(i128::from) == Some(label))
+                .map(|(_, v)| v)
+        };
+        let bytes_field = |label: i128| field(label).and_then(|v| v.as_bytes()).ok_or_else(invalid);
+        let alg = field(3).and_then(|v| v.as_integer()).map(i128::from).ok_or_else(invalid)?;
+
+        match alg {
+            COSE_ALG_ES256 => {
+                let (x, y) = (bytes_field(-2)?, bytes_field(-3)?);
+                let mut sec1 = Vec::with_capacity(65);
+                sec1.push(0x04);
+                sec1.extend_from_slice(x);
+                sec1.extend_from_slice(y);
+                p256::ecdsa::VerifyingKey::from_sec1_bytes(&sec1)
+                    .map(Self::Es256)
+                    .map_err(|_| invalid())
+            }
+            COSE_ALG_EDDSA => {
+                let x: [u8; 32] = bytes_field(-2)?.as_slice().try_into().map_err(|_| invalid())?;
+                ed25519_dalek::VerifyingKey::from_bytes(&x)
+                    .map(Self::EdDsa)
+                    .map_err(|_| invalid())
+            }
+            COSE_ALG_RS256 => {
+                let n = rsa::BigUint::from_bytes_be(bytes_field(-1)?);
+                let e = rsa::BigUint::from_bytes_be(bytes_field(-2)?);
+                rsa::RsaPublicKey::new(n, e)
+                    .map(|k| Self::Rs256(rsa::pkcs1v15::VerifyingKey::new(k)))
+                    .map_err(|_| invalid())
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Checks an assertion signature over `authenticatorData || SHA-256(clientDataJSON)`.
+    pub fn verify_assertion(&self, auth_data: &[u8], client_data_json: &[u8], signature: &[u8]) -> bool {
+        use p256::ecdsa::signature::Verifier as _;
+
+        let mut message = auth_data.to_vec();
+        message.extend_from_slice(&Sha256::digest(client_data_json));
+
+        match self {
+            Self::Es256(key) => p256::ecdsa::Signature::from_der(signature)
+                .map(|sig| key.verify(&message, &sig).is_ok())
+                .unwrap_or(false),
+            Self::EdDsa(key) => ed25519_dalek::Signature::from_slice(signature)
+                .map(|sig| key.verify_strict(&message, &sig).is_ok())
+                .unwrap_or(false),
+            Self::Rs256(key) => rsa::pkcs1v15::Signature::try_from(signature)
+                .map(|sig| key.verify(&message, &sig).is_ok())
+                .unwrap_or(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argon2::password_hash::rand_core::OsRng;
+
+    const RP_ID: &str = "tikiya.app";
+
+    fn cbor(value: &Value) -> Vec<u8> {
+        let mut out = Vec::new();
+        ciborium::into_writer(value, &mut out).unwrap();
+        out
+    }
+
+    fn int(v: i64) -> Value {
+        Value::Integer(v.into())
+    }
+
+    fn auth_data(flags: u8, sign_count: u32, attested: Option<(&[u8], &[u8])>) -> Vec<u8> {
+        let mut data = Sha256::digest(RP_ID.as_bytes()).to_vec();
+        data.push(flags);
+        data.extend_from_slice(&sign_count.to_be_bytes());
+        if let Some((credential_id, public_key)) = attested {
+            data.extend_from_slice(&[0u8; 16]);
+            data.extend_from_slice(&(credential_id.len() as u16).to_be_bytes());
+            data.extend_from_slice(credential_id);
+            data.extend_from_slice(public_key);
+        }
+        data
+    }
+
+    fn es256_key() -> (p256::ecdsa::SigningKey, Vec<u8>) {
+        let signing = p256::ecdsa::SigningKey::random(&mut OsRng);
+        let point = signing.verifying_key().to_encoded_point(false);
+        let cose = cbor(&Value::Map(vec![
+            (int(1), int(2)),
+            (int(3), int(-7)),
+            (int(-1), int(1)),
+            (int(-2), Value::Bytes(point.x().unwrap().to_vec())),
+            (int(-3), Value::Bytes(point.y().unwrap().to_vec())),
+        ]));
+        (signing, cose)
+    }
+
+    fn signed_message(auth_data: &[u8], client_data_json: &[u8]) -> Vec<u8> {
+        let mut message = auth_data.to_vec();
+        message.extend_from_slice(&Sha256::digest(client_data_json));
+        message
+    }
+
+    #[test]
+    fn parses_flags_counter_and_rp_id() {
+        let data = auth_data(FLAG_USER_PRESENT | FLAG_USER_VERIFIED, 42, None);
+        let parsed = parse_authenticator_data(&data).unwrap();
+        assert!(parsed.rp_id_matches(RP_ID));
+        assert!(!parsed.rp_id_matches("evil.example"));
+        assert!(parsed.user_present());
+        assert!(parsed.user_verified());
+        assert_eq!(parsed.sign_count, 42);
+        assert!(parsed.attested.is_none());
+
+        let data = auth_data(FLAG_USER_PRESENT, 0, None);
+        assert!(!parse_authenticator_data(&data).unwrap().user_verified());
+    }
+
+    #[test]
+    fn rejects_short_authenticator_data() {
+        assert!(parse_authenticator_data(&[0u8; 36]).is_err());
+    }
+
+    #[test]
+    fn parses_attested_credential_followed_by_extensions() {
+        let (_, cose) = es256_key();
+        let mut data = auth_data(FLAG_USER_PRESENT | FLAG_ATTESTED_CREDENTIAL, 0, Some((b"cred-1", &cose)));
+        // Extension data (flag ED) after the key must not end up in the stored key.
+        data.extend_from_slice(&cbor(&Value::Map(vec![(Value::Text("credProtect".into()), int(2))])));
+
+        let parsed = parse_authenticator_data(&data).unwrap();
+        let attested = parsed.attested.unwrap();
+        assert_eq!(attested.credential_id, b"cred-1");
+        assert_eq!(attested.public_key, cose);
+    }
+
+    #[test]
+    fn rejects_truncated_attested_credential() {
+        let (_, cose) = es256_key();
+        let data = auth_data(FLAG_USER_PRESENT | FLAG_ATTESTED_CREDENTIAL, 0, Some((b"cred-1", &cose)));
+        // Credential id length pointing past the end.
+        let mut long_id = data.clone();
+        long_id[37 + 16] = 0xff;
+        assert!(parse_authenticator_data(&long_id).is_err());
+        // Public key cut short.
+        assert!(parse_authenticator_data(&data[..data.len() - 5]).is_err());
+        // Flag set without any credential data.
+        assert!(parse_authenticator_data(&auth_data(FLAG_ATTESTED_CREDENTIAL, 0, None)).is_err());
+    }
+
+    #[test]
+    fn extracts_auth_data_from_attestation_object() {
+        let object = cbor(&Value::Map(vec![
+            (Value::Text("fmt".into()), Value::Text("none".into())),
+            (Value::Text("attStmt".into()), Value::Map(vec![])),
+            (Value::Text("authData".into()), Value::Bytes(vec![1, 2, 3])),
+        ]));
+        assert_eq!(attestation_auth_data(&object).unwrap(), vec![1, 2, 3]);
+
+        let missing = cbor(&Value::Map(vec![(Value::Text("fmt".into()), Value::Text("none".into()))]));
+        assert!(attestation_auth_data(&missing).is_err());
+        assert!(attestation_auth_data(&cbor(&Value::Array(vec![]))).is_err());
+        assert!(attestation_auth_data(b"\xff\x00").is_err());
+    }
+
+    #[test]
+    fn parses_client_data_and_padded_base64url() {
+        let raw = br#"{"type":"webauthn.get","challenge":"abc","origin":"https://tikiya.app","crossOrigin":false}"#;
+        let client_data = parse_client_data(raw).unwrap();
+        assert_eq!(client_data.kind, "webauthn.get");
+        assert_eq!(client_data.challenge, "abc");
+        assert_eq!(client_data.origin, "https://tikiya.app");
+        assert!(parse_client_data(br#"{"type":"webauthn.get"}"#).is_err());
+
+        assert_eq!(decode_b64url("aGk=").unwrap(), b"hi");
+        assert_eq!(decode_b64url("aGk").unwrap(), b"hi");
+        assert!(decode_b64url("a+b/").is_err());
+    }
+
+    #[test]
+    fn verifies_es256_assertions() {
+        use p256::ecdsa::signature::Signer as _;
+
+        let (signing, cose) = es256_key();
+        let key = CosePublicKey::from_cbor(&cose).unwrap();
+        let data = auth_data(FLAG_USER_PRESENT, 1, None);
+        let client_data_json = br#"{"type":"webauthn.get"}"#;
+        let signature: p256::ecdsa::Signature = signing.sign(&signed_message(&data, client_data_json));
+        let der = signature.to_der();
+
+        assert!(key.verify_assertion(&data, client_data_json, der.as_bytes()));
+        assert!(!key.verify_assertion(&auth_data(FLAG_USER_PRESENT, 2, None), client_data_json, der.as_bytes()));
+        assert!(!key.verify_assertion(&data, br#"{"type":"webauthn.create"}"#, der.as_bytes()));
+        // Raw r||s is not the DER encoding WebAuthn uses.
+        assert!(!key.verify_assertion(&data, client_data_json, &signature.to_bytes()));
+    }
+
+    #[test]
+    fn verifies_eddsa_assertions() {
+        use ed25519_dalek::Signer as _;
+
+        let signing = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+        let cose = cbor(&Value::Map(vec![
+            (int(1), int(1)),
+            (int(3), int(-8)),
+            (int(-1), int(6)),
+            (int(-2), Value::Bytes(signing.verifying_key().to_bytes().to_vec())),
+        ]));
+        let key = CosePublicKey::from_cbor(&cose).unwrap();
+        let data = auth_data(FLAG_USER_PRESENT, 0, None);
+        let client_data_json = b"{}";
+        let signature = signing.sign(&signed_message(&data, client_data_json)).to_bytes();
+
+        assert!(key.verify_assertion(&data, client_data_json, &signature));
+        let mut tampered = signature;
+        tampered[0] ^= 1;
+        assert!(!key.verify_assertion(&data, client_data_json, &tampered));
+    }
+
+    #[test]
+    fn verifies_rs256_assertions() {
+        use rsa::signature::{SignatureEncoding as _, Signer as _};
+        use rsa::traits::PublicKeyParts as _;
+
+        let private = rsa::RsaPrivateKey::new(&mut OsRng, 1024).unwrap();
+        let cose = cbor(&Value::Map(vec![
+            (int(1), int(3)),
+            (int(3), int(-257)),
+            (int(-1), Value::Bytes(private.n().to_bytes_be())),
+            (int(-2), Value::Bytes(private.e().to_bytes_be())),
+        ]));
+        let key = CosePublicKey::from_cbor(&cose).unwrap();
+        let data = auth_data(FLAG_USER_PRESENT, 0, None);
+        let client_data_json = b"{}";
+        let signing = rsa::pkcs1v15::SigningKey::<Sha256>::new(private);
+        let signature = signing.sign(&signed_message(&data, client_data_json)).to_vec();
+
+        assert!(key.verify_assertion(&data, client_data_json, &signature));
+        assert!(!key.verify_assertion(&data, b"[]", &signature));
+    }
+
+    #[test]
+    fn rejects_unsupported_or_malformed_keys() {
+        let (_, cose) = es256_key();
+        let Value::Map(mut fields) = ciborium::from_reader::<Value, _>(cose.as_slice()).unwrap() else {
+            unreachable!()
+        };
+
+        // PS256 is not offered in pubKeyCredParams.
+        let mut other_alg = fields.clone();
+        other_alg[1].1 = int(-37);
+        assert!(CosePublicKey::from_cbor(&cbor(&Value::Map(other_alg))).is_err());
+
+        // Coordinates that are not a point on P-256.
+        let mut off_curve = fields.clone();
+        off_curve[4].1 = Value::Bytes(vec![0u8; 32]);
+        assert!(CosePublicKey::from_cbor(&cbor(&Value::Map(off_curve))).is_err());
+
+        // Missing y coordinate.
+        fields.truncate(4);
+        assert!(CosePublicKey::from_cbor(&cbor(&Value::Map(fields))).is_err());
+
+        // Ed25519 key of the wrong length, and input that is not a CBOR map.
+        let short_ed25519 = cbor(&Value::Map(vec![(int(3), int(-8)), (int(-2), Value::Bytes(vec![1u8; 31]))]));
+        assert!(CosePublicKey::from_cbor(&short_ed25519).is_err());
+        assert!(CosePublicKey::from_cbor(&cbor(&int(1))).is_err());
+    }
+}
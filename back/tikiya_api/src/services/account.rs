@@ -0,0 +1,389 @@
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::dto::{AccountDeletionResponse, AccountExport, AuthResponse, ExportedOrganization, ExportedSession, UserResponse};
+use crate::error::ApiError;
+use crate::models::{AccountStatus, User, USER_COLUMNS};
+use crate::security::client_info::ClientInfo;
+use crate::security::tokens::{hmac_token, random_token};
+use crate::services::audit::{self, AuditEvent, Outcome};
+use crate::services::auth::AuthService;
+use crate::services::cleanup;
+use crate::services::identities::IdentityService;
+use crate::services::mail::Email;
+use crate::services::mfa::MfaService;
+use crate::services::organizer::OrganizerService;
+use crate::services::webauthn::WebauthnService;
+use crate::state::AppState;
+
+/// How often the server looks for accounts whose deletion grace period is over, and purges
+/// expired one-time rows.
+const PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+/// Time left to confirm the cancel offered at sign-in.
+const CANCEL_TOKEN_TTL_MINUTES: i64 = 15;
+
+/// Personal data rights: export of the caller's data and self-service deletion.
+pub struct AccountService {
+    state: AppState,
+}
+
+impl AccountService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    pub async fn export(&self, user_id: Uuid) -> Result<AccountExport, ApiError> {
+        let user = sqlx::query_as::<_, User>(&format!("SELECT {USER_COLUMNS} FROM users WHERE id = $1"))
+            .bind(user_id)
+            .fetch_optional(&self.state.db.pool)
+            .await?
+            .ok_or(ApiError::Unauthorized)?;
+
+        #[derive(sqlx::FromRow)]
+        struct SessionRow {
+            id: Uuid,
+            device_label: Option<String>,
+            user_agent: Option<String>,
+            ip: Option<String>,
+            created_at: DateTime<Utc>,
+            last_used_at: Option<DateTime<Utc>>,
+            expires_at: DateTime<Utc>,
+            revoked_at: Option<DateTime<Utc>>,
+        }
+        let sessions = sqlx::query_as::<_, SessionRow>(
+            "SELECT id, device_label, user_agent, ip, created_at, last_used_at, expires_at, revoked_at FROM sessions WHERE user_id = $1 ORDER BY created_at",
+        )
+        .bind(user_id)
+        .fetch_all(&self.state.db.pool)
+        .await?;
+
+        #[derive(sqlx::FromRow)]
+        struct OrganizationRow {
+            id: Uuid,
+            name: String,
+            legal_id: String,
+            contact_email: String,
+            contact_phone: String,
+            created_at: DateTime<Utc>,
+        }
+        let organizations = sqlx::query_as::<_, OrganizationRow>(
+            "SELECT id, name, legal_id, contact_email, contact_phone, created_at FROM organizations WHERE owner_user_id = $1 ORDER BY created_at",
+        )
+        .bind(user_id)
+        .fetch_all(&self.state.db.pool)
+        .await?;
+
+        let export = AccountExport {
+            exported_at: Utc::now(),
+            profile: UserResponse::from(&user),
+            has_password: user.password_hash.is_some(),
+            totp_enabled: MfaService::new(self.state.clone()).totp_enabled(user_id).await?,
+            sessions: sessions
+                .into_iter()
+                .map(|row| ExportedSession {
+                    id: row.id,
+                    device_label: row.device_label,
+                    user_agent: row.user_agent,
+                    ip: row.ip,
+                    created_at: row.created_at,
+                    last_used_at: row.last_used_at,
+                    expires_at: row.expires_at,
+                    revoked_at: row.revoked_at,
+                })
+                .collect(),
+            identities: IdentityService::new(self.state.clone()).list(user_id).await?,
+            passkeys: WebauthnService::new(self.state.clone()).list_credentials(user_id).await?,
+            organizer_applications: OrganizerService::new(self.state.clone()).list_for_user(user_id).await?,
+            organizations: organizations
+                .into_iter()
+                .map(|row| ExportedOrganization {
+                    id: row.id,
+                    name: row.name,
+                    legal_id: row.legal_id,
+                    contact_email: row.contact_email,
+                    contact_phone: row.contact_phone,
+                    created_at: row.created_at,
+                })
+                .collect(),
+        };
+
+        tracing::info!(user_id = %user_id, "account.export");
+        Ok(export)
+    }
+
+    /// Marks the account `pending_deletion` and signs it out everywhere, once the caller passed
+    /// [`ReauthService`](crate::services::reauth::ReauthService). The data is erased by
+    /// [`purge_due`](Self::purge_due) once the grace period is over; until then the user can
+    /// cancel by signing in, or an admin can reactivate the account.
+    pub async fn schedule_deletion(&self, user_id: Uuid, client: &ClientInfo) -> Result<AccountDeletionResponse, ApiError> {
+        let email = sqlx::query_scalar::<_, Option<String>>("SELECT email FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.state.db.pool)
+            .await?
+            .ok_or(ApiError::Unauthorized)?;
+
+        let deletion_at = Utc::now() + Duration::days(self.state.config.account_deletion_grace_days);
+        let mut tx = self.state.db.pool.begin().await?;
+        sqlx::query(
+            "UPDATE users SET status = 'pending_deletion', status_reason = NULL, status_until = $2, status_changed_at = NOW() WHERE id = $1",
+        )
+        .bind(user_id)
+        .bind(deletion_at)
+        .execute(&mut *tx)
+        .await?;
+        let revoked = sqlx::query("UPDATE sessions SET revoked_at = NOW(), revoked_reason = 'account_deletion' WHERE user_id = $1 AND revoked_at IS NULL")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        let event = AuditEvent::new("account.deletion_scheduled", Outcome::Success, client)
+            .user(user_id)
+            .details(json!({ "deletion_at": deletion_at, "revoked_sessions": revoked }));
+        audit::record(&mut tx, &event).await?;
+        tx.commit().await?;
+
+        tracing::info!(user_id = %user_id, deletion_at = %deletion_at, revoked_sessions = revoked, "account.deletion.scheduled");
+
+        if let Some(to) = email {
+            let body = format!(
+                "Bonjour,\n\nLa suppression de votre compte Tikiya a été demandée. Vos données seront effacées le {}.\n\nPour l'annuler, reconnectez-vous avant cette date. Si vous n'êtes pas à l'origine de cette demande, contactez-nous.",
+                deletion_at.format("%d/%m/%Y")
+            );
+            let result = self
+                .state
+                .mailer
+                .send(Email {
+                    to,
+                    subject: "Suppression de votre compte Tikiya".to_string(),
+                    body,
+                })
+                .await;
+            if let Err(err) = result {
+                tracing::error!(user_id = %user_id, error = %err, "account.deletion.mail_failed");
+            }
+        }
+
+        Ok(AccountDeletionResponse {
+            status: AccountStatus::PendingDeletion,
+            deletion_scheduled_at: deletion_at,
+        })
+    }
+
+    /// Single-use token offered after a successful sign-in on an account in its deletion grace
+    /// period. `second_factor` records whether that sign-in proved two factors.
+    pub async fn offer_deletion_cancel(&self, user_id: Uuid, second_factor: bool) -> Result<String, ApiError> {
+        let token = random_token(32);
+        sqlx::query(
+            "INSERT INTO deletion_cancel_tokens (token_hash, user_id, second_factor, expires_at) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(hmac_token(&self.state.config.one_time_token_secret, &token)?)
+        .bind(user_id)
+        .bind(second_factor)
+        .bind(Utc::now() + Duration::minutes(CANCEL_TOKEN_TTL_MINUTES))
+        .execute(&self.state.db.pool)
+        .await?;
+        tracing::info!(user_id = %user_id, "account.deletion.cancel_offered");
+        Ok(token)
+    }
+
+    /// Exchanges a token from [`offer_deletion_cancel`](Self::offer_deletion_cancel): the account
+    /// is active again and signed in, as the sign-in that earned the token would have been.
+    pub async fn cancel_deletion(&self, token: &str, client: &ClientInfo) -> Result<AuthResponse, ApiError> {
+        let token_hash = hmac_token(&self.state.config.one_time_token_secret, token)?;
+        let invalid = || ApiError::Validation("invalid or expired cancel token".into());
+        let mut tx = self.state.db.pool.begin().await?;
+
+        let (user_id, second_factor) = sqlx::query_as::<_, (Uuid, bool)>(
+            "UPDATE deletion_cancel_tokens SET consumed_at = NOW() \
+             WHERE token_hash = $1 AND consumed_at IS NULL AND expires_at > NOW() RETURNING user_id, second_factor",
+        )
+        .bind(&token_hash)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(invalid)?;
+
+        let user = sqlx::query_as::<_, User>(&format!(
+            "UPDATE users SET status = 'active', status_reason = NULL, status_until = NULL, status_changed_at = NOW() \
+             WHERE id = $1 AND status = 'pending_deletion' AND status_until > NOW() RETURNING {USER_COLUMNS}"
+        ))
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(invalid)?;
+
+        let event = AuditEvent::new("account.deletion_cancelled", Outcome::Success, client).user(user_id);
+        audit::record(&mut tx, &event).await?;
+        tx.commit().await?;
+        tracing::info!(user_id = %user_id, "account.deletion.cancelled");
+
+        if let Some(to) = user.email.clone() {
+            let result = self
+                .state
+                .mailer
+                .send(Email {
+                    to,
+                    subject: "Suppression de votre compte Tikiya annulée".to_string(),
+                    body: "Bonjour,\n\nLa suppression de votre compte Tikiya a été annulée à votre connexion. Votre compte reste actif.\n\nSi vous n'êtes pas à l'origine de cette connexion, changez votre mot de passe.".to_string(),
+                })
+                .await;
+            if let Err(err) = result {
+                tracing::error!(user_id = %user_id, error = %err, "account.deletion.cancel_mail_failed");
+            }
+        }
+
+        let tokens = AuthService::new(self.state.clone())
+            .issue_tokens(&user, client, second_factor)
+            .await?;
+        Ok(AuthResponse {
+            user: UserResponse::from(&user),
+            tokens,
+        })
+    }
+
+    /// Erases the accounts whose grace period is over. Returns how many were processed.
+    pub async fn purge_due(&self) -> Result<u64, ApiError> {
+        let due = sqlx::query_scalar::<_, Uuid>(
+            "SELECT id FROM users WHERE status = 'pending_deletion' AND status_until <= NOW() ORDER BY status_until LIMIT 100",
+        )
+        .fetch_all(&self.state.db.pool)
+        .await?;
+
+        let mut purged = 0;
+        for user_id in due {
+            match self.erase(user_id).await {
+                Ok(()) => purged += 1,
+                Err(err) => tracing::error!(user_id = %user_id, error = %err, "account.deletion.erase_failed"),
+            }
+        }
+        Ok(purged)
+    }
+
+    /// Deletes the user, or anonymizes the row when records that must be kept reference it
+    /// (organizations it owns; orders once they exist). Either way, credentials, sessions,
+    /// identities and pending tokens go.
+    async fn erase(&self, user_id: Uuid) -> Result<(), ApiError> {
+        let mut tx = self.state.db.pool.begin().await?;
+        let Some((email, phone)) = sqlx::query_as::<_, (Option<String>, Option<String>)>(
+            "SELECT email, phone FROM users WHERE id = $1 AND status = 'pending_deletion' AND status_until <= NOW() FOR UPDATE",
+        )
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            // Reactivated or already erased meanwhile.
+            return Ok(());
+        };
+
+        // Tokens keyed by address rather than by user id.
+        if let Some(email) = email.as_deref() {
+            sqlx::query("DELETE FROM magic_link_tokens WHERE lower(email) = lower($1)")
+                .bind(email)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("DELETE FROM login_throttles WHERE scope = 'ip_account' AND split_part(key, '|', 2) = lower($1)")
+                .bind(email)
+                .execute(&mut *tx)
+                .await?;
+        }
+        if let Some(phone) = phone.as_deref() {
+            sqlx::query("DELETE FROM phone_otps WHERE phone = $1")
+                .bind(phone)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        // The audit trail outlives the account, minus what identifies the person.
+        sqlx::query(
+            "UPDATE audit_events SET ip = NULL, user_agent = NULL, details = details - 'email' \
+             WHERE subject_user_id = $1 OR actor_user_id = $1 OR ($2::text IS NOT NULL AND lower(details->>'email') = lower($2))",
+        )
+        .bind(user_id)
+        .bind(email.as_deref())
+        .execute(&mut *tx)
+        .await?;
+
+        let retained = sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM organizations WHERE owner_user_id = $1)")
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        if retained {
+            for table in [
+                "sessions",
+                "user_identities",
+                "webauthn_credentials",
+                "webauthn_challenges",
+                "user_mfa_totp",
+                "mfa_recovery_codes",
+                "mfa_challenges",
+                "email_verification_tokens",
+                "password_reset_tokens",
+                "deletion_cancel_tokens",
+            ] {
+                sqlx::query(&format!("DELETE FROM {table} WHERE user_id = $1"))
+                    .bind(user_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            // Approved applications document the organization and stay with it, without the
+            // contact person's details or the supporting documents (identity papers).
+            sqlx::query("DELETE FROM organizer_applications WHERE user_id = $1 AND status <> 'approved'")
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(
+                "DELETE FROM organizer_application_documents WHERE application_id IN \
+                 (SELECT id FROM organizer_applications WHERE user_id = $1)",
+            )
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query(
+                "UPDATE organizer_applications SET contact_name = NULL, contact_email = NULL, contact_phone = NULL WHERE user_id = $1",
+            )
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query(
+                "UPDATE users SET email = NULL, password_hash = NULL, first_name = NULL, last_name = NULL, phone = NULL, \
+                    preferred_language = NULL, city = NULL, email_verified_at = NULL, phone_verified_at = NULL, \
+                    failed_attempts = 0, lockout_until = NULL, status = 'deleted', status_reason = NULL, status_until = NULL, \
+                    status_changed_at = NOW(), deleted_at = NOW() \
+                 WHERE id = $1",
+            )
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        } else {
+            sqlx::query("DELETE FROM users WHERE id = $1")
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        tracing::info!(user_id = %user_id, anonymized = retained, "account.deletion.erased");
+        Ok(())
+    }
+}
+
+/// Background task erasing accounts whose deletion grace period is over and deleting expired
+/// tokens, challenges and login throttle counters.
+pub async fn run_purge_loop(state: AppState) {
+    let service = AccountService::new(state);
+    let mut interval = tokio::time::interval(PURGE_INTERVAL);
+    loop {
+        interval.tick().await;
+        match service.purge_due().await {
+            Ok(0) => {}
+            Ok(purged) => tracing::info!(purged, "account.deletion.purge"),
+            Err(err) => tracing::error!(error = %err, "account.deletion.purge_failed"),
+        }
+        match cleanup::purge_expired(&service.state).await {
+            Ok(0) => {}
+            Ok(purged) => tracing::info!(purged, "maintenance.expired_rows.purge"),
+            Err(err) => tracing::error!(error = %err, "maintenance.expired_rows.purge_failed"),
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::dto::{AdminUserPage, AdminUserResponse};
+use crate::error::{ApiError, FieldError};
+use crate::models::{AccountStatus, Role, User, USER_COLUMNS};
+use crate::security::client_info::ClientInfo;
+use crate::services::audit::{self, AuditEvent, Outcome};
+use crate::services::login_throttle::LoginThrottleService;
+use crate::services::pagination::{decode_cursor, encode_cursor, MAX_PAGE_SIZE};
+use crate::state::AppState;
+
+/// Filters of `GET /admin/users`; every field is optional.
+#[derive(Debug, Default)]
+pub struct UserFilter {
+    /// Case-insensitive substring of the email.
+    pub email: Option<String>,
+    pub role: Option<Role>,
+    pub status: Option<AccountStatus>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+}
+
+pub struct AdminUserService {
+    state: AppState,
+}
+
+impl AdminUserService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    /// Users matching `filter`, newest first. Keyset pagination on `(created_at, id)`, so pages
+    /// stay stable while accounts are created.
+    pub async fn list(&self, filter: UserFilter, cursor: Option<&str>, limit: i64) -> Result<AdminUserPage, ApiError> {
+        let limit = limit.clamp(1, MAX_PAGE_SIZE);
+        let after = cursor.map(decode_cursor).transpose()?;
+        let email_pattern = filter
+            .email
+            .as_deref()
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+            .map(|e| format!("%{}%", escape_like(e)));
+
+        let mut users = sqlx::query_as::<_, User>(&format!(
+            "SELECT {USER_COLUMNS} FROM users \
+             WHERE ($1::text IS NULL OR email ILIKE $1) \
+               AND ($2::user_role IS NULL OR role = $2) \
+               AND ($3::account_status IS NULL OR status = $3) \
+               AND ($4::timestamptz IS NULL OR created_at >= $4) \
+               AND ($5::timestamptz IS NULL OR created_at < $5) \
+               AND ($6::timestamptz IS NULL OR (created_at, id) < ($6, $7)) \
+             ORDER BY created_at DESC, id DESC LIMIT $8"
+        ))
+        .bind(email_pattern)
+        .bind(filter.role)
+        .bind(filter.status)
+        .bind(filter.created_after)
+        .bind(filter.created_before)
+        .bind(after.map(|(created_at, _)| created_at))
+        .bind(after.map(|(_, id)| id))
+        .bind(limit + 1)
+        .fetch_all(&self.state.db.pool)
+        .await?;
+
+        let next_cursor = if users.len() as i64 > limit {
+            users.truncate(limit as usize);
+            users.last().map(|last| encode_cursor(last.created_at, last.id))
+        } else {
+            None
+        };
+
+        Ok(AdminUserPage {
+            users: users.iter().map(AdminUserResponse::from).collect(),
+            next_cursor,
+        })
+    }
+
+    pub async fn get(&self, user_id: Uuid) -> Result<AdminUserResponse, ApiError> {
+        let user = sqlx::query_as::<_, User>(&format!("SELECT {USER_COLUMNS} FROM users WHERE id = $1"))
+            .bind(user_id)
+            .fetch_optional(&self.state.db.pool)
+            .await?
+            .ok_or(ApiError::NotFound)?;
+        Ok(AdminUserResponse::from(&user))
+    }
+
+    /// Sets the role. A change that can take away access revokes every session of the user;
+    /// role-gated routes also re-check the role, so a demotion applies at once.
+    pub async fn change_role(&self, admin_id: Uuid, client: &ClientInfo, user_id: Uuid, role: Role) -> Result<AdminUserResponse, ApiError> {
+        if user_id == admin_id {
+            return Err(ApiError::Conflict("admins cannot change their own role".into()));
+        }
+
+        let mut tx = self.state.db.pool.begin().await?;
+        let previous = sqlx::query_scalar::<_, Role>("SELECT role FROM users WHERE id = $1 FOR UPDATE")
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(ApiError::NotFound)?;
+
+        let user = sqlx::query_as::<_, User>(&format!("UPDATE users SET role = $2 WHERE id = $1 RETURNING {USER_COLUMNS}"))
+            .bind(user_id)
+            .bind(role)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        // Access tokens carry the role: sign a demoted user out so no session keeps the old one.
+        let revoked = if previous.loses_access_to(role) {
+            sqlx::query("UPDATE sessions SET revoked_at = NOW(), revoked_reason = 'role_changed' WHERE user_id = $1 AND revoked_at IS NULL")
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected()
+        } else {
+            0
+        };
+
+        let event = AuditEvent::new("admin.user.role_changed", Outcome::Success, client)
+            .actor(admin_id)
+            .subject(Some(user_id))
+            .details(json!({ "from": previous, "to": role, "revoked_sessions": revoked }));
+        audit::record(&mut tx, &event).await?;
+        tx.commit().await?;
+
+        Ok(AdminUserResponse::from(&user))
+    }
+
+    /// Clears the login backoff of the account (every IP) and the legacy lockout columns.
+    pub async fn unlock(&self, admin_id: Uuid, client: &ClientInfo, user_id: Uuid) -> Result<AdminUserResponse, ApiError> {
+        let mut tx = self.state.db.pool.begin().await?;
+        let user = sqlx::query_as::<_, User>(&format!(
+            "UPDATE users SET failed_attempts = 0, lockout_until = NULL WHERE id = $1 RETURNING {USER_COLUMNS}"
+        ))
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+        let cleared = match user.email.as_deref() {
+            Some(email) => LoginThrottleService::new(self.state.clone()).clear_account(email).await?,
+            None => 0,
+        };
+
+        let event = AuditEvent::new("admin.user.unlocked", Outcome::Success, client)
+            .actor(admin_id)
+            .subject(Some(user_id))
+            .details(json!({ "cleared_throttles": cleared }));
+        audit::record(&mut tx, &event).await?;
+        tx.commit().await?;
+
+        Ok(AdminUserResponse::from(&user))
+    }
+
+    /// Suspends, bans or reactivates the account, until `until` or indefinitely. Leaving
+    /// `active` revokes every session.
+    pub async fn set_status(
+        &self,
+        admin_id: Uuid,
+        client: &ClientInfo,
+        user_id: Uuid,
+        status: AccountStatus,
+        reason: Option<&str>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<AdminUserResponse, ApiError> {
+        if user_id == admin_id {
+            return Err(ApiError::Conflict("admins cannot change their own status".into()));
+        }
+        if until.is_some_and(|until| until <= Utc::now()) {
+            return Err(ApiError::InvalidFields(vec![FieldError::new("until", "in_past", "must be in the future")]));
+        }
+        let reason = reason.map(str::trim).filter(|r| !r.is_empty());
+
+        let mut tx = self.state.db.pool.begin().await?;
+        let previous = sqlx::query_scalar::<_, AccountStatus>("SELECT status FROM users WHERE id = $1 FOR UPDATE")
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(ApiError::NotFound)?;
+
+        let user = sqlx::query_as::<_, User>(&format!(
+            "UPDATE users SET status = $2, status_reason = $3, status_until = $4, status_changed_at = NOW() WHERE id = $1 RETURNING {USER_COLUMNS}"
+        ))
+        .bind(user_id)
+        .bind(status)
+        .bind(reason)
+        .bind(until)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let revoked = if status == AccountStatus::Active {
+            0
+        } else {
+            sqlx::query("UPDATE sessions SET revoked_at = NOW(), revoked_reason = $2 WHERE user_id = $1 AND revoked_at IS NULL")
+                .bind(user_id)
+                .bind(format!("account_{status}"))
+                .execute(&mut *tx)
+                .await?
+                .rows_affected()
+        };
+
+        let action = match status {
+            AccountStatus::Active => "admin.user.reactivated",
+            AccountStatus::Suspended => "admin.user.suspended",
+            AccountStatus::Banned => "admin.user.banned",
+            AccountStatus::PendingDeletion => "admin.user.deletion_scheduled",
+            AccountStatus::Deleted => "admin.user.deleted",
+        };
+        let event = AuditEvent::new(action, Outcome::Success, client)
+            .actor(admin_id)
+            .subject(Some(user_id))
+            .details(json!({ "from": previous, "reason": reason, "until": until, "revoked_sessions": revoked }));
+        audit::record(&mut tx, &event).await?;
+        tx.commit().await?;
+
+        Ok(AdminUserResponse::from(&user))
+    }
+
+    /// Signs the user out everywhere. Returns how many sessions were revoked.
+    pub async fn revoke_sessions(&self, admin_id: Uuid, client: &ClientInfo, user_id: Uuid) -> Result<u64, ApiError> {
+        let mut tx = self.state.db.pool.begin().await?;
+        let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM users WHERE id = $1)")
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        if !exists {
+            return Err(ApiError::NotFound);
+        }
+
+        let revoked = sqlx::query("UPDATE sessions SET revoked_at = NOW(), revoked_reason = 'admin_revoked' WHERE user_id = $1 AND revoked_at IS NULL")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        let event = AuditEvent::new("admin.user.sessions_revoked", Outcome::Success, client)
+            .actor(admin_id)
+            .subject(Some(user_id))
+            .details(json!({ "revoked_sessions": revoked }));
+        audit::record(&mut tx, &event).await?;
+        tx.commit().await?;
+
+        Ok(revoked)
+    }
+}
+
+/// Escapes `LIKE` wildcards so the search is a plain substring match.
+fn escape_like(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
@@ -0,0 +1,217 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::dto::{AuditEventPage, AuditEventResponse};
+use crate::error::ApiError;
+use crate::security::client_info::ClientInfo;
+use crate::services::pagination::{decode_cursor, encode_cursor, MAX_PAGE_SIZE};
+use crate::state::AppState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Success,
+    Failure,
+}
+
+impl Outcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Outcome::Success => "success",
+            Outcome::Failure => "failure",
+        }
+    }
+}
+
+/// One row of `audit_events`. Build with [`AuditEvent::new`], then set who acted, whose
+/// account it concerns and event-specific details.
+#[derive(Debug)]
+pub struct AuditEvent {
+    /// Dotted name, e.g. `auth.login` or `admin.user.role_changed`.
+    pub event_type: &'static str,
+    pub outcome: Outcome,
+    pub actor_user_id: Option<Uuid>,
+    pub subject_user_id: Option<Uuid>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub request_id: Option<String>,
+    pub details: serde_json::Value,
+}
+
+impl AuditEvent {
+    pub fn new(event_type: &'static str, outcome: Outcome, client: &ClientInfo) -> Self {
+        Self {
+            event_type,
+            outcome,
+            actor_user_id: None,
+            subject_user_id: None,
+            ip: client.ip_string(),
+            user_agent: client.user_agent.clone(),
+            request_id: client.request_id.clone(),
+            details: serde_json::Value::Object(Default::default()),
+        }
+    }
+
+    /// The user acted on their own account.
+    pub fn user(mut self, user_id: Uuid) -> Self {
+        self.actor_user_id = Some(user_id);
+        self.subject_user_id = Some(user_id);
+        self
+    }
+
+    pub fn actor(mut self, user_id: Uuid) -> Self {
+        self.actor_user_id = Some(user_id);
+        self
+    }
+
+    pub fn subject(mut self, user_id: Option<Uuid>) -> Self {
+        self.subject_user_id = user_id;
+        self
+    }
+
+    pub fn details(mut self, details: serde_json::Value) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// Records the event. Call it inside the action's transaction when the trail must not miss a
+/// committed change (admin actions).
+pub async fn record(conn: &mut PgConnection, event: &AuditEvent) -> Result<(), ApiError> {
+    sqlx::query(
+        "INSERT INTO audit_events (event_type, outcome, actor_user_id, subject_user_id, ip, user_agent, request_id, details) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8::jsonb)",
+    )
+    .bind(event.event_type)
+    .bind(event.outcome.as_str())
+    .bind(event.actor_user_id)
+    .bind(event.subject_user_id)
+    .bind(&event.ip)
+    .bind(&event.user_agent)
+    .bind(&event.request_id)
+    .bind(event.details.to_string())
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Records the event on its own connection. Best effort: a failed write is logged and does not
+/// fail the request (sign-ins keep working if the trail is unavailable).
+pub async fn record_detached(state: &AppState, event: AuditEvent) {
+    let result = match state.db.pool.acquire().await {
+        Ok(mut conn) => record(&mut conn, &event).await,
+        Err(err) => Err(ApiError::from(err)),
+    };
+    if let Err(err) = result {
+        tracing::error!(event_type = event.event_type, error = %err, "audit.record_failed");
+    }
+}
+
+/// Filters of `GET /admin/audit-events`; every field is optional.
+#[derive(Debug, Default)]
+pub struct AuditFilter {
+    pub subject_user_id: Option<Uuid>,
+    pub actor_user_id: Option<Uuid>,
+    pub event_type: Option<String>,
+    pub outcome: Option<String>,
+    pub ip: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+pub struct AuditService {
+    state: AppState,
+}
+
+impl AuditService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    /// Events matching `filter`, newest first, keyset-paginated on `(created_at, id)`.
+    pub async fn query(&self, filter: AuditFilter, cursor: Option<&str>, limit: i64) -> Result<AuditEventPage, ApiError> {
+        #[derive(sqlx::FromRow)]
+        struct EventRow {
+            id: Uuid,
+            event_type: String,
+            outcome: String,
+            actor_user_id: Option<Uuid>,
+            subject_user_id: Option<Uuid>,
+            ip: Option<String>,
+            user_agent: Option<String>,
+            request_id: Option<String>,
+            details: String,
+            created_at: DateTime<Utc>,
+        }
+
+        let limit = limit.clamp(1, MAX_PAGE_SIZE);
+        let after = cursor.map(decode_cursor).transpose()?;
+
+        let mut rows = sqlx::query_as::<_, EventRow>(
+            "SELECT id, event_type, outcome, actor_user_id, subject_user_id, ip, user_agent, request_id, details::text AS details, created_at \
+             FROM audit_events \
+             WHERE ($1::uuid IS NULL OR subject_user_id = $1) \
+               AND ($2::uuid IS NULL OR actor_user_id = $2) \
+               AND ($3::text IS NULL OR event_type = $3) \
+               AND ($4::text IS NULL OR outcome = $4) \
+               AND ($5::text IS NULL OR ip = $5) \
+               AND ($6::timestamptz IS NULL OR created_at >= $6) \
+               AND ($7::timestamptz IS NULL OR created_at < $7) \
+               AND ($8::timestamptz IS NULL OR (created_at, id) < ($8, $9)) \
+             ORDER BY created_at DESC, id DESC LIMIT $10",
+        )
+        .bind(filter.subject_user_id)
+        .bind(filter.actor_user_id)
+        .bind(filter.event_type)
+        .bind(filter.outcome)
+        .bind(filter.ip)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(after.map(|(created_at, _)| created_at))
+        .bind(after.map(|(_, id)| id))
+        .bind(limit + 1)
+        .fetch_all(&self.state.db.pool)
+        .await?;
+
+        let next_cursor = if rows.len() as i64 > limit {
+            rows.truncate(limit as usize);
+            rows.last().map(|last| encode_cursor(last.created_at, last.id))
+        } else {
+            None
+        };
+
+        Ok(AuditEventPage {
+            events: rows
+                .into_iter()
+                .map(|row| AuditEventResponse {
+                    id: row.id,
+                    event_type: row.event_type,
+                    outcome: row.outcome,
+                    actor_user_id: row.actor_user_id,
+                    subject_user_id: row.subject_user_id,
+                    ip: row.ip,
+                    user_agent: row.user_agent,
+                    request_id: row.request_id,
+                    details: serde_json::from_str(&row.details).unwrap_or_default(),
+                    created_at: row.created_at,
+                })
+                .collect(),
+            next_cursor,
+        })
+    }
+
+    /// The caller's own security events. Which admin acted on the account is not disclosed.
+    pub async fn list_for_user(&self, user_id: Uuid, cursor: Option<&str>, limit: i64) -> Result<AuditEventPage, ApiError> {
+        let filter = AuditFilter {
+            subject_user_id: Some(user_id),
+            ..AuditFilter::default()
+        };
+        let mut page = self.query(filter, cursor, limit).await?;
+        for event in &mut page.events {
+            if event.actor_user_id != Some(user_id) {
+                event.actor_user_id = None;
+            }
+        }
+        Ok(page)
+    }
+}
@@ -1,36 +1,60 @@
 use argon2::password_hash::{
     rand_core::{OsRng, RngCore},
-    PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+    PasswordHash, PasswordVerifier,
 };
 use argon2::Argon2;
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use chrono::{Duration, Utc};
 use hmac::{Hmac, Mac};
-use jsonwebtoken::{encode, EncodingKey, Header};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use sha2::Sha256;
+use sqlx::PgConnection;
+use tokio::sync::OnceCell;
 use uuid::Uuid;
 
-use crate::dto::{AuthResponse, AuthTokens, LoginRequest, LogoutRequest, RefreshRequest, RegisterRequest, UserResponse};
+use crate::dto::{AuthResponse, AuthTokens, ChangePasswordRequest, LoginRequest, LoginResponse, LogoutRequest, RefreshRequest, RegisterRequest, UserResponse};
 use crate::error::ApiError;
-use crate::models::User;
+use crate::models::{AccountStatus, Role, User, USER_COLUMNS};
+use crate::security::client_info::ClientInfo;
+use crate::security::password::PasswordHashing;
+use crate::security::phone::normalize_e164;
+use crate::security::tokens::{hmac_token, random_token};
+use crate::services::account::AccountService;
+use crate::services::audit::{self, AuditEvent, Outcome};
+use crate::services::email_verification::EmailVerificationService;
+use crate::services::login_throttle::LoginThrottleService;
+use crate::services::mfa::{second_factor_throttle_key, MfaService};
+use crate::services::sessions::SessionService;
 use crate::state::AppState;
 
 const ACCESS_TOKEN_TTL_MINUTES: i64 = 15;
 const REFRESH_TOKEN_TTL_DAYS: i64 = 30;
+/// A superseded refresh secret presented this soon after rotation is a client retry, not a replay.
+const REFRESH_REUSE_GRACE_SECS: i64 = 10;
 
 pub struct AuthService {
     pub(crate) state: AppState,
 }
 
-#[derive(Debug, Serialize)]
-struct Claims {
-    sub: Uuid,
-    email: String,
-    exp: usize,
-    iat: usize,
-    aud: String,
-    iss: String,
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct Claims {
+    pub sub: Uuid,
+    #[serde(default)]
+    pub email: Option<String>,
+    pub role: Role,
+    #[serde(default)]
+    pub email_verified: bool,
+    /// Session (refresh token) this access token was issued for.
+    #[serde(default)]
+    pub sid: Option<Uuid>,
+    /// Set when the session was opened (or later confirmed) with a second factor.
+    #[serde(default)]
+    pub mfa: bool,
+    pub exp: usize,
+    pub iat: usize,
+    pub aud: String,
+    pub iss: String,
 }
 
 impl AuthService {
@@ -38,20 +62,42 @@ impl AuthService {
         Self { state }
     }
 
-    pub async fn register(&self, payload: RegisterRequest) -> Result<AuthResponse, ApiError> {
+    pub async fn register(&self, payload: RegisterRequest, client: &ClientInfo) -> Result<AuthResponse, ApiError> {
+        let phone = non_blank(payload.phone.as_deref())
+            .map(|phone| normalize_e164(phone, &self.state.config.phone_default_country_code))
+            .transpose()?;
+        let user_inputs: Vec<&str> = [&payload.first_name, &payload.last_name, &payload.city]
+            .into_iter()
+            .filter_map(|v| v.as_deref())
+            .chain(phone.as_deref())
+            .collect();
+        self.state
+            .password_policy
+            .check("password", &payload.password, Some(&payload.email), &user_inputs)?;
         let password_hash = self.hash_password(&payload.password).await?;
 
         let user = sqlx::query_as::<_, User>(
-            "INSERT INTO users (email, password_hash) VALUES ($1, $2) RETURNING id, email, password_hash, role, oauth_provider, oauth_subject, created_at, failed_attempts, lockout_until"
+            &format!("INSERT INTO users (email, password_hash, first_name, last_name, phone, preferred_language, city) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING {USER_COLUMNS}")
         )
         .bind(&payload.email)
         .bind(password_hash)
+        .bind(non_blank(payload.first_name.as_deref()))
+        .bind(non_blank(payload.last_name.as_deref()))
+        .bind(phone)
+        .bind(non_blank(payload.preferred_language.as_deref()))
+        .bind(non_blank(payload.city.as_deref()))
         .fetch_one(&self.state.db.pool)
         .await?;
 
-        let tokens = self.issue_tokens(&user).await?;
+        let tokens = self.issue_tokens(&user, client, false).await?;
 
-        tracing::info!(user_id = %user.id, email = %user.email, "auth.register.success");
+        tracing::info!(user_id = %user.id, email = ?user.email, "auth.register.success");
+
+        // The account is usable right away; verification only gates purchases and organizer actions.
+        let verification = EmailVerificationService::new(self.state.clone());
+        if let Err(err) = verification.send_verification(&user).await {
+            tracing::error!(user_id = %user.id, error = %err, "auth.register.verification_mail_failed");
+        }
 
         Ok(AuthResponse {
             user: UserResponse::from(&user),
@@ -59,79 +105,96 @@ impl AuthService {
         })
     }
 
-    pub async fn login(&self, payload: LoginRequest) -> Result<AuthResponse, ApiError> {
-        let user = self
-            .find_user_by_email(&payload.email)
-            .await?
-            .ok_or(ApiError::Unauthorized)?;
+    pub async fn login(&self, payload: LoginRequest, client: &ClientInfo) -> Result<LoginResponse, ApiError> {
+        let throttle = LoginThrottleService::new(self.state.clone());
+        if let Err(err) = throttle.check(client.ip, &payload.email).await {
+            let event = AuditEvent::new("auth.login", Outcome::Failure, client)
+                .details(json!({ "method": "password", "reason": "throttled", "email": payload.email }));
+            audit::record_detached(&self.state, event).await;
+            return Err(err);
+        }
 
-        // DB-backed lockout check
-        if let Some(until) = user.lockout_until {
-            if until > Utc::now() {
-                tracing::warn!(email = %payload.email, "auth.login.locked_out_db");
-                return Err(ApiError::Unauthorized);
+        let user = self.find_user_by_email(&payload.email).await?;
+
+        // Unknown emails and password-less accounts still pay for an Argon2 verification, so the
+        // response time does not tell whether the account exists.
+        let stored_hash = user.as_ref().and_then(|u| u.password_hash.clone());
+        let ok = match stored_hash.as_deref() {
+            Some(hash) => self.verify_password(hash, &payload.password).await?,
+            None => {
+                let dummy = self.dummy_password_hash().await?;
+                self.verify_password(&dummy, &payload.password).await?;
+                false
             }
-        }
+        };
 
-        let hash = user
-            .password_hash
-            .as_deref()
-            .ok_or(ApiError::Unauthorized)?;
+        let known_user_id = user.as_ref().map(|u| u.id);
+        let Some(user) = user.filter(|_| ok) else {
+            tracing::warn!(email = %payload.email, ip = ?client.ip, "auth.login.invalid_credentials");
+            let event = AuditEvent::new("auth.login", Outcome::Failure, client)
+                .subject(known_user_id)
+                .details(json!({ "method": "password", "reason": "invalid_credentials", "email": payload.email }));
+            audit::record_detached(&self.state, event).await;
+            return Err(throttle.record_failure(client.ip, &payload.email).await?);
+        };
 
-        // Argon2 is CPU-bound: run it on the blocking thread pool.
-        let password = payload.password.clone();
-        let hash_str = hash.to_string();
-        let ok = tokio::task::spawn_blocking(move || {
-            let parsed = PasswordHash::new(&hash_str).map_err(|_| ())?;
-            let argon2 = Argon2::default();
-            argon2
-                .verify_password(password.as_bytes(), &parsed)
-                .map(|_| ())
-                .map_err(|_| ())
-        })
-        .await
-        .map_err(|_| ApiError::Internal)
-        ?
-        .is_ok();
-
-        if !ok {
-            tracing::warn!(email = %payload.email, "auth.login.invalid_password");
-            // Increment failed attempts and set lockout if threshold reached
-            let attempts = user.failed_attempts + 1;
-            let mut lockout_until: Option<chrono::DateTime<Utc>> = None;
-            if attempts >= 5 {
-                lockout_until = Some(Utc::now() + Duration::minutes(15));
-            }
-            sqlx::query("UPDATE users SET failed_attempts = $1, lockout_until = $2 WHERE id = $3")
-                .bind(attempts)
-                .bind(lockout_until)
-                .bind(user.id)
-                .execute(&self.state.db.pool)
-                .await?;
-            return Err(ApiError::Unauthorized);
+        throttle.record_success(client.ip, &payload.email).await?;
+        if let Some(hash) = stored_hash.as_deref() {
+            self.rehash_if_outdated(user.id, hash, &payload.password).await;
         }
+        self.complete_login(&user, client, "password", false).await
+    }
 
-        // Reset failed attempts only if needed (avoid a write on every successful login)
-        if user.failed_attempts != 0 || user.lockout_until.is_some() {
-            sqlx::query("UPDATE users SET failed_attempts = 0, lockout_until = NULL WHERE id = $1")
-                .bind(user.id)
-                .execute(&self.state.db.pool)
+    /// Finishes a successful first-factor login: issues tokens, or an MFA challenge when the
+    /// account has a second factor enrolled. `method` names the first factor in the audit trail;
+    /// `second_factor` is set when it already proved two factors (a user-verified passkey), which
+    /// skips the challenge.
+    pub async fn complete_login(
+        &self,
+        user: &User,
+        client: &ClientInfo,
+        method: &str,
+        second_factor: bool,
+    ) -> Result<LoginResponse, ApiError> {
+        if let Err(err) = ensure_can_authenticate(user) {
+            let event = AuditEvent::new("auth.login", Outcome::Failure, client)
+                .subject(Some(user.id))
+                .details(json!({ "method": method, "reason": format!("account_{}", user.status) }));
+            audit::record_detached(&self.state, event).await;
+            return Err(err);
+        }
+
+        let mfa = MfaService::new(self.state.clone());
+        if !second_factor && mfa.totp_enabled(user.id).await? {
+            // No fresh challenge, and so no fresh guesses, while second-factor attempts are backed off.
+            LoginThrottleService::new(self.state.clone())
+                .ensure_not_blocked(client.ip, &second_factor_throttle_key(user.id))
                 .await?;
+            tracing::info!(user_id = %user.id, "auth.login.mfa_challenge");
+            let event = AuditEvent::new("auth.login.first_factor", Outcome::Success, client)
+                .user(user.id)
+                .details(json!({ "method": method, "mfa_required": true }));
+            audit::record_detached(&self.state, event).await;
+            return Ok(LoginResponse::MfaRequired(mfa.create_challenge(user.id).await?));
         }
 
-        let tokens = self.issue_tokens(&user).await?;
+        let tokens = self.issue_tokens(user, client, second_factor).await?;
 
-        tracing::info!(user_id = %user.id, email = %user.email, "auth.login.success");
+        tracing::info!(user_id = %user.id, email = ?user.email, "auth.login.success");
+        let event = AuditEvent::new("auth.login", Outcome::Success, client)
+            .user(user.id)
+            .details(json!({ "method": method, "second_factor": second_factor }));
+        audit::record_detached(&self.state, event).await;
 
-        Ok(AuthResponse {
-            user: UserResponse::from(&user),
+        Ok(LoginResponse::Authenticated(AuthResponse {
+            user: UserResponse::from(user),
             tokens,
-        })
+        }))
     }
 
     async fn find_user_by_email(&self, email: &str) -> Result<Option<User>, ApiError> {
         let user = sqlx::query_as::<_, User>(
-            "SELECT id, email, password_hash, role, oauth_provider, oauth_subject, created_at, failed_attempts, lockout_until FROM users WHERE email = $1"
+            &format!("SELECT {USER_COLUMNS} FROM users WHERE email = $1")
         )
         .bind(email)
         .fetch_optional(&self.state.db.pool)
@@ -140,23 +203,134 @@ impl AuthService {
         Ok(user)
     }
 
-    async fn hash_password(&self, password: &str) -> Result<String, ApiError> {
+    /// Hash of a random password, computed once, verified against when there is no real hash.
+    async fn dummy_password_hash(&self) -> Result<String, ApiError> {
+        static DUMMY_HASH: OnceCell<String> = OnceCell::const_new();
+        DUMMY_HASH
+            .get_or_try_init(|| async { self.hash_password(&random_token(16)).await })
+            .await
+            .cloned()
+    }
+
+    pub(crate) async fn hash_password(&self, password: &str) -> Result<String, ApiError> {
         let password = password.to_string();
-        Ok(tokio::task::spawn_blocking(move || {
-            let salt = SaltString::generate(&mut OsRng);
-            let argon2 = Argon2::default();
-            argon2
-                .hash_password(password.as_bytes(), &salt)
-                .map(|h| h.to_string())
-        })
-        .await
-        .map_err(|_| ApiError::Internal)??)
+        let config = self.state.config.password_hash.clone();
+        tokio::task::spawn_blocking(move || PasswordHashing::new(&config).hash(&password))
+            .await
+            .map_err(|_| ApiError::Internal)?
+    }
+
+    /// Checks `password` against a stored Argon2 hash. Malformed hashes count as a mismatch.
+    pub(crate) async fn verify_password(&self, hash: &str, password: &str) -> Result<bool, ApiError> {
+        // Argon2 is CPU-bound: run it on the blocking thread pool.
+        let password = password.to_string();
+        let hash_str = hash.to_string();
+        let config = self.state.config.password_hash.clone();
+        tokio::task::spawn_blocking(move || PasswordHashing::new(&config).verify(&hash_str, &password))
+            .await
+            .map_err(|_| ApiError::Internal)
+    }
+
+    /// Replaces a hash computed with outdated Argon2 parameters or pepper, right after the
+    /// password was verified against it. Best effort: the login goes on if this fails.
+    async fn rehash_if_outdated(&self, user_id: Uuid, current_hash: &str, password: &str) {
+        if !PasswordHashing::new(&self.state.config.password_hash).needs_rehash(current_hash) {
+            return;
+        }
+        let result = async {
+            let new_hash = self.hash_password(password).await?;
+            // Compare-and-swap: a password changed meanwhile is not overwritten.
+            sqlx::query("UPDATE users SET password_hash = $1 WHERE id = $2 AND password_hash = $3")
+                .bind(&new_hash)
+                .bind(user_id)
+                .bind(current_hash)
+                .execute(&self.state.db.pool)
+                .await?;
+            Ok::<_, ApiError>(())
+        }
+        .await;
+        match result {
+            Ok(()) => tracing::info!(user_id = %user_id, "auth.password.rehashed"),
+            Err(err) => tracing::error!(user_id = %user_id, error = %err, "auth.password.rehash_failed"),
+        }
     }
 
-    pub async fn issue_tokens(&self, user: &User) -> Result<AuthTokens, ApiError> {
-        let access_token = self.generate_access_token(user.id, &user.email)?;
+    /// Sets a new password, once the caller passed [`ReauthService`](crate::services::reauth::ReauthService)
+    /// (the current password, or for password-less accounts a second factor or a recent sign-in).
+    /// Optionally revokes every other session.
+    pub async fn change_password(
+        &self,
+        user_id: Uuid,
+        current_session: Option<Uuid>,
+        payload: ChangePasswordRequest,
+        client: &ClientInfo,
+    ) -> Result<(), ApiError> {
+        let (current_hash, email, first_name, last_name) =
+            sqlx::query_as::<_, (Option<String>, Option<String>, Option<String>, Option<String>)>(
+                "SELECT password_hash, email, first_name, last_name FROM users WHERE id = $1",
+            )
+            .bind(user_id)
+            .fetch_optional(&self.state.db.pool)
+            .await?
+            .ok_or(ApiError::Unauthorized)?;
+
+        let user_inputs: Vec<&str> = [&first_name, &last_name].into_iter().filter_map(|v| v.as_deref()).collect();
+        self.state
+            .password_policy
+            .check("new_password", &payload.new_password, email.as_deref(), &user_inputs)?;
+
+        let new_hash = self.hash_password(&payload.new_password).await?;
+        sqlx::query("UPDATE users SET password_hash = $1 WHERE id = $2")
+            .bind(&new_hash)
+            .bind(user_id)
+            .execute(&self.state.db.pool)
+            .await?;
+
+        let mut revoked = 0;
+        if payload.revoke_other_sessions {
+            revoked = SessionService::new(self.state.clone())
+                .revoke_all(user_id, current_session)
+                .await?;
+        }
+
+        tracing::info!(
+            user_id = %user_id,
+            first_password = current_hash.is_none(),
+            revoked_sessions = revoked,
+            "auth.password_change.success"
+        );
+        let event = AuditEvent::new("auth.password.changed", Outcome::Success, client)
+            .user(user_id)
+            .details(json!({ "first_password": current_hash.is_none(), "revoked_sessions": revoked }));
+        audit::record_detached(&self.state, event).await;
+        Ok(())
+    }
+
+    /// Opens a session. An account in its deletion grace period gets no session; the sign-in
+    /// having succeeded, the refusal carries a token to cancel the deletion instead.
+    pub async fn issue_tokens(&self, user: &User, client: &ClientInfo, mfa: bool) -> Result<AuthTokens, ApiError> {
+        if deletion_cancellable(user) {
+            let cancel_token = AccountService::new(self.state.clone())
+                .offer_deletion_cancel(user.id, mfa)
+                .await?;
+            return Err(ApiError::AccountDisabled {
+                status: user.status,
+                reason: None,
+                until: user.status_until,
+                cancel_token: Some(cancel_token),
+            });
+        }
+        ensure_can_sign_in(user)?;
         let (secret, secret_hash, refresh_exp) = self.generate_refresh_secret()?;
-        let session_id = self.persist_session(user, &secret_hash, refresh_exp).await?;
+        let session_id = self.persist_session(user, &secret_hash, refresh_exp, client, mfa).await?;
+        let access_token = self.generate_access_token(
+            user.id,
+            user.email.as_deref(),
+            user.role,
+            user.email_verified_at.is_some(),
+            session_id,
+            mfa,
+        )?;
         let refresh_token = format!("{}.{}", session_id, secret);
 
         Ok(AuthTokens {
@@ -165,27 +339,31 @@ impl AuthService {
         })
     }
 
-    fn generate_access_token(&self, user_id: Uuid, email: &str) -> Result<String, ApiError> {
+    fn generate_access_token(
+        &self,
+        user_id: Uuid,
+        email: Option<&str>,
+        role: Role,
+        email_verified: bool,
+        session_id: Uuid,
+        mfa: bool,
+    ) -> Result<String, ApiError> {
         let now = Utc::now();
         let exp = now + Duration::minutes(ACCESS_TOKEN_TTL_MINUTES);
         let claims = Claims {
             sub: user_id,
-            email: email.to_string(),
+            email: email.map(str::to_string),
+            role,
+            email_verified,
+            sid: Some(session_id),
+            mfa,
             iat: now.timestamp() as usize,
             exp: exp.timestamp() as usize,
             aud: self.state.config.jwt_audience.clone(),
             iss: self.state.config.jwt_issuer.clone(),
         };
 
-        encode(
-            &Header::default(),
-            &claims,
-            &EncodingKey::from_secret(self.state.config.jwt_secret.as_bytes()),
-        )
-        .map_err(|err| {
-            tracing::error!(?err, "auth.token.encode_failed");
-            ApiError::Internal
-        })
+        self.state.jwt_keys.sign(&claims)
     }
 
     fn generate_refresh_secret(&self) -> Result<(String, String, chrono::DateTime<Utc>), ApiError> {
@@ -202,12 +380,7 @@ impl AuthService {
     }
 
     fn hmac_refresh_secret(&self, secret: &str) -> Result<String, ApiError> {
-        // Use JWT secret as the key by default; can be separated later if desired.
-        let key = self.state.config.jwt_secret.as_bytes();
-        let mut mac = Hmac::<Sha256>::new_from_slice(key).map_err(|_| ApiError::Internal)?;
-        mac.update(secret.as_bytes());
-        let tag = mac.finalize().into_bytes();
-        Ok(format!("hmac:{}", URL_SAFE_NO_PAD.encode(tag)))
+        hmac_token(&self.state.config.refresh_token_secret, secret)
     }
 
     async fn verify_refresh_secret(&self, stored_hash: &str, secret: &str) -> Result<(), ApiError> {
@@ -216,12 +389,27 @@ impl AuthService {
             let expected = URL_SAFE_NO_PAD
                 .decode(b64)
                 .map_err(|_| ApiError::Unauthorized)?;
-            let key = self.state.config.jwt_secret.as_bytes();
-            let mut mac = Hmac::<Sha256>::new_from_slice(key).map_err(|_| ApiError::Internal)?;
-            mac.update(secret.as_bytes());
-            mac.verify_slice(&expected)
-                .map_err(|_| ApiError::Unauthorized)?;
-            return Ok(());
+            let matches = |key: &str| -> Result<bool, ApiError> {
+                let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes()).map_err(|_| ApiError::Internal)?;
+                mac.update(secret.as_bytes());
+                Ok(mac.verify_slice(&expected).is_ok())
+            };
+            if matches(&self.state.config.refresh_token_secret)? {
+                return Ok(());
+            }
+            // Hashed with JWT_SECRET before the refresh key was derived from it.
+            let config = &self.state.config;
+            if let (true, Some(legacy)) = (config.refresh_accept_legacy_hashes, config.refresh_token_legacy_secret.as_deref()) {
+                if matches(legacy)? {
+                    return Ok(());
+                }
+            }
+            return Err(ApiError::Unauthorized);
+        }
+
+        if !self.state.config.refresh_accept_legacy_hashes {
+            tracing::warn!("auth.refresh.legacy_hash_rejected");
+            return Err(ApiError::Unauthorized);
         }
 
         // Legacy Argon2 verification is CPU-bound as well.
@@ -246,37 +434,52 @@ impl AuthService {
         user: &User,
         refresh_hash: &str,
         expires_at: chrono::DateTime<Utc>,
+        client: &ClientInfo,
+        mfa: bool,
     ) -> Result<Uuid, ApiError> {
         let id = sqlx::query_scalar::<_, Uuid>(
-            "INSERT INTO sessions (user_id, token_hash, expires_at) VALUES ($1, $2, $3) RETURNING id",
+            "INSERT INTO sessions (user_id, token_hash, expires_at, user_agent, ip, device_label, mfa, last_used_at) VALUES ($1, $2, $3, $4, $5, $6, $7, NOW()) RETURNING id",
         )
             .bind(user.id)
             .bind(refresh_hash)
             .bind(expires_at)
+            .bind(&client.user_agent)
+            .bind(client.ip_string())
+            .bind(&client.device_label)
+            .bind(mfa)
             .fetch_one(&self.state.db.pool)
             .await?;
 
         Ok(id)
     }
 
-    pub async fn refresh(&self, payload: RefreshRequest) -> Result<AuthTokens, ApiError> {
+    pub async fn refresh(&self, payload: RefreshRequest, client: &ClientInfo) -> Result<AuthTokens, ApiError> {
         let (session_id, secret) = parse_refresh_token(&payload.refresh_token)?;
 
         #[derive(sqlx::FromRow)]
         struct SessionRow {
             id: Uuid,
             user_id: Uuid,
-            email: String,
+            email: Option<String>,
+            role: Role,
+            email_verified_at: Option<chrono::DateTime<Utc>>,
             token_hash: String,
             expires_at: chrono::DateTime<Utc>,
             revoked_at: Option<chrono::DateTime<Utc>>,
+            mfa: bool,
+            status: AccountStatus,
+            status_reason: Option<String>,
+            status_until: Option<chrono::DateTime<Utc>>,
         }
 
+        // Lock the session row: concurrent refreshes of the same family are serialised.
+        let mut tx = self.state.db.pool.begin().await?;
+
         let session = sqlx::query_as::<_, SessionRow>(
-            "SELECT s.id, s.user_id, u.email, s.token_hash, s.expires_at, s.revoked_at FROM sessions s JOIN users u ON u.id = s.user_id WHERE s.id = $1",
+            "SELECT s.id, s.user_id, u.email, u.role, u.email_verified_at, s.token_hash, s.expires_at, s.revoked_at, s.mfa, u.status, u.status_reason, u.status_until FROM sessions s JOIN users u ON u.id = s.user_id WHERE s.id = $1 FOR UPDATE OF s",
         )
         .bind(session_id)
-        .fetch_optional(&self.state.db.pool)
+        .fetch_optional(&mut *tx)
         .await?
         .ok_or(ApiError::Unauthorized)?;
 
@@ -284,38 +487,125 @@ impl AuthService {
             return Err(ApiError::Unauthorized);
         }
 
-        self.verify_refresh_secret(&session.token_hash, secret).await?;
+        if self.verify_refresh_secret(&session.token_hash, secret).await.is_err() {
+            self.detect_refresh_reuse(&mut tx, session.id, session.user_id, secret, client)
+                .await?;
+            tx.commit().await?;
+            return Err(ApiError::Unauthorized);
+        }
+
+        check_account_status(session.user_id, session.status, session.status_reason, session.status_until)?;
 
         // Issue new tokens and rotate session hash
-        let access_token = self.generate_access_token(session.user_id, &session.email)?;
+        let access_token = self.generate_access_token(
+            session.user_id,
+            session.email.as_deref(),
+            session.role,
+            session.email_verified_at.is_some(),
+            session.id,
+            session.mfa,
+        )?;
         let (new_secret, new_hash, new_exp) = self.generate_refresh_secret()?;
         let refresh_token = format!("{}.{}", session.id, new_secret);
 
-        sqlx::query("UPDATE sessions SET token_hash = $1, expires_at = $2 WHERE id = $3")
+        // History holds the HMAC of the superseded secret even when the session still had a
+        // legacy Argon2 hash: reuse detection compares HMACs, and the legacy hash is gone after this.
+        if !session.token_hash.starts_with("hmac:") {
+            tracing::info!(session_id = %session.id, "auth.refresh.legacy_hash_migrated");
+        }
+        sqlx::query("INSERT INTO session_secret_history (session_id, token_hash) VALUES ($1, $2)")
+            .bind(session.id)
+            .bind(self.hmac_refresh_secret(secret)?)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            "UPDATE sessions SET token_hash = $1, expires_at = $2, last_used_at = NOW(), ip = COALESCE($4, ip), user_agent = COALESCE($5, user_agent) WHERE id = $3",
+        )
             .bind(new_hash)
             .bind(new_exp)
             .bind(session.id)
-            .execute(&self.state.db.pool)
+            .bind(client.ip_string())
+            .bind(&client.user_agent)
+            .execute(&mut *tx)
             .await?;
 
+        let event = AuditEvent::new("auth.refresh", Outcome::Success, client)
+            .user(session.user_id)
+            .details(json!({ "session_id": session.id }));
+        audit::record(&mut tx, &event).await?;
+        tx.commit().await?;
+
         Ok(AuthTokens {
             access_token,
             refresh_token,
         })
     }
 
-    pub async fn logout(&self, payload: LogoutRequest) -> Result<(), ApiError> {
+    /// Called when a refresh secret does not match the session's current one. If it matches a
+    /// superseded secret of the same family, the token was replayed after rotation (stolen or
+    /// leaked): revoke the whole family. Presentations right after a rotation are treated as a
+    /// client retry race and only rejected.
+    async fn detect_refresh_reuse(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        session_id: Uuid,
+        user_id: Uuid,
+        secret: &str,
+        client: &ClientInfo,
+    ) -> Result<(), ApiError> {
+        let presented_hash = self.hmac_refresh_secret(secret)?;
+        let superseded_at = sqlx::query_scalar::<_, chrono::DateTime<Utc>>(
+            "SELECT superseded_at FROM session_secret_history WHERE session_id = $1 AND token_hash = $2 ORDER BY superseded_at DESC LIMIT 1",
+        )
+        .bind(session_id)
+        .bind(&presented_hash)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        let Some(superseded_at) = superseded_at else {
+            return Ok(());
+        };
+
+        if superseded_at > Utc::now() - Duration::seconds(REFRESH_REUSE_GRACE_SECS) {
+            tracing::info!(session_id = %session_id, user_id = %user_id, "auth.refresh.concurrent_rotation");
+            return Ok(());
+        }
+
+        sqlx::query(
+            "UPDATE sessions SET revoked_at = NOW(), revoked_reason = 'refresh_token_reuse' WHERE id = $1 AND revoked_at IS NULL",
+        )
+        .bind(session_id)
+        .execute(&mut **tx)
+        .await?;
+
+        tracing::warn!(
+            session_id = %session_id,
+            user_id = %user_id,
+            ip = ?client.ip,
+            user_agent = ?client.user_agent,
+            superseded_at = %superseded_at,
+            "security.refresh_token_reuse"
+        );
+        let event = AuditEvent::new("auth.refresh.reuse_detected", Outcome::Failure, client)
+            .subject(Some(user_id))
+            .details(json!({ "session_id": session_id, "superseded_at": superseded_at }));
+        audit::record(tx, &event).await?;
+        Ok(())
+    }
+
+    pub async fn logout(&self, payload: LogoutRequest, client: &ClientInfo) -> Result<(), ApiError> {
         let (session_id, secret) = parse_refresh_token(&payload.refresh_token)?;
 
-        let row = sqlx::query_as::<_, (String, chrono::DateTime<Utc>, Option<chrono::DateTime<Utc>>)>(
-            "SELECT token_hash, expires_at, revoked_at FROM sessions WHERE id = $1",
+        let row = sqlx::query_as::<_, (Uuid, String, chrono::DateTime<Utc>, Option<chrono::DateTime<Utc>>)>(
+            "SELECT user_id, token_hash, expires_at, revoked_at FROM sessions WHERE id = $1",
         )
         .bind(session_id)
         .fetch_optional(&self.state.db.pool)
         .await?
         .ok_or(ApiError::Unauthorized)?;
 
-        let (token_hash, expires_at, revoked_at) = row;
+        let (user_id, token_hash, expires_at, revoked_at) = row;
         if revoked_at.is_some() || expires_at <= Utc::now() {
             return Err(ApiError::Unauthorized);
         }
@@ -327,10 +617,94 @@ impl AuthService {
             .execute(&self.state.db.pool)
             .await?;
 
+        let event = AuditEvent::new("auth.logout", Outcome::Success, client)
+            .user(user_id)
+            .details(json!({ "session_id": session_id }));
+        audit::record_detached(&self.state, event).await;
         Ok(())
     }
 }
 
+/// First proof of mailbox ownership on an account whose email was never verified: whoever
+/// registered it may not own the address, so everything they set up is dropped (password,
+/// verified phone, passkeys, TOTP, sessions, and provider identities that did not vouch for this
+/// email). Runs in the caller's transaction; returns the number of revoked sessions.
+pub(crate) async fn discard_unproven_credentials(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    email: &str,
+) -> Result<u64, ApiError> {
+    sqlx::query("UPDATE users SET password_hash = NULL, phone = NULL, phone_verified_at = NULL WHERE id = $1")
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("DELETE FROM user_identities WHERE user_id = $1 AND NOT (email_verified AND lower(email) = lower($2))")
+        .bind(user_id)
+        .bind(email)
+        .execute(&mut *conn)
+        .await?;
+    for table in ["webauthn_credentials", "user_mfa_totp", "mfa_recovery_codes", "mfa_challenges"] {
+        sqlx::query(&format!("DELETE FROM {table} WHERE user_id = $1"))
+            .bind(user_id)
+            .execute(&mut *conn)
+            .await?;
+    }
+    let revoked = sqlx::query(
+        "UPDATE sessions SET revoked_at = NOW(), revoked_reason = 'email_ownership_proven' WHERE user_id = $1 AND revoked_at IS NULL",
+    )
+    .bind(user_id)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+    tracing::warn!(user_id = %user_id, revoked_sessions = revoked, "auth.unverified_account.credentials_discarded");
+    Ok(revoked)
+}
+
+/// Rejects sign-ins (and token refreshes) of suspended, banned or deleting accounts.
+pub(crate) fn ensure_can_sign_in(user: &User) -> Result<(), ApiError> {
+    check_account_status(user.id, user.status, user.status_reason.clone(), user.status_until)
+}
+
+/// Same as [`ensure_can_sign_in`], but lets an account in its deletion grace period through the
+/// factor checks, so that [`AuthService::issue_tokens`] can offer to cancel the deletion.
+pub(crate) fn ensure_can_authenticate(user: &User) -> Result<(), ApiError> {
+    if deletion_cancellable(user) {
+        return Ok(());
+    }
+    ensure_can_sign_in(user)
+}
+
+fn deletion_cancellable(user: &User) -> bool {
+    user.status == AccountStatus::PendingDeletion && user.status_until.is_some_and(|until| until > Utc::now())
+}
+
+fn check_account_status(
+    user_id: Uuid,
+    status: AccountStatus,
+    reason: Option<String>,
+    until: Option<chrono::DateTime<Utc>>,
+) -> Result<(), ApiError> {
+    if status.allows_sign_in(until, Utc::now()) {
+        return Ok(());
+    }
+    tracing::warn!(user_id = %user_id, status = %status, "auth.account_disabled");
+    Err(ApiError::AccountDisabled { status, reason, until, cancel_token: None })
+}
+
+/// Validates an access token minted by `generate_access_token` (signature, `exp`, `aud`, `iss`).
+/// Only [`AuthUser`](crate::security::auth_user::AuthUser) trusts the `role` claim as-is; role-gated
+/// routes (`RequireRole`) re-check the role and the session in the database.
+pub(crate) fn decode_access_token(state: &AppState, token: &str) -> Result<Claims, ApiError> {
+    state
+        .jwt_keys
+        .verify(token, &state.config.jwt_audience, &state.config.jwt_issuer)
+}
+
+/// Trims optional profile input; blank strings are stored as NULL.
+pub(crate) fn non_blank(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|v| !v.is_empty())
+}
+
 fn parse_refresh_token(token: &str) -> Result<(Uuid, &str), ApiError> {
     let (sid, secret) = token.split_once('.').ok_or(ApiError::Unauthorized)?;
     let session_id = Uuid::parse_str(sid).map_err(|_| ApiError::Unauthorized)?;
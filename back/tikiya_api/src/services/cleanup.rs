@@ -0,0 +1,44 @@
+use chrono::{Duration, Utc};
+
+use crate::error::ApiError;
+use crate::state::AppState;
+
+/// Expired rows are kept this long before being deleted: the per-email, per-number and per-user
+/// send limits count recent rows, so they must outlive the longest of those windows.
+const EXPIRED_RETENTION_HOURS: i64 = 48;
+
+/// Single-use tokens and challenges, purged once expired.
+const EXPIRING_TABLES: &[&str] = &[
+    "oauth_states",
+    "magic_link_tokens",
+    "phone_otps",
+    "mfa_challenges",
+    "webauthn_challenges",
+    "email_verification_tokens",
+    "password_reset_tokens",
+    "deletion_cancel_tokens",
+];
+
+/// Deletes expired single-use rows and stale login throttle counters. Run from the hourly
+/// maintenance loop; returns the number of rows deleted.
+pub async fn purge_expired(state: &AppState) -> Result<u64, ApiError> {
+    let cutoff = Utc::now() - Duration::hours(EXPIRED_RETENTION_HOURS);
+    let mut purged = 0;
+    for table in EXPIRING_TABLES {
+        purged += sqlx::query(&format!("DELETE FROM {table} WHERE expires_at < $1"))
+            .bind(cutoff)
+            .execute(&state.db.pool)
+            .await?
+            .rows_affected();
+    }
+
+    let window_start = Utc::now() - Duration::seconds(state.config.login_throttle.failure_window_secs);
+    purged += sqlx::query(
+        "DELETE FROM login_throttles WHERE last_failure_at < $1 AND (blocked_until IS NULL OR blocked_until < NOW())",
+    )
+    .bind(window_start)
+    .execute(&state.db.pool)
+    .await?
+    .rows_affected();
+    Ok(purged)
+}
@@ -0,0 +1,133 @@
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::models::{User, USER_COLUMNS};
+use crate::security::tokens::{hmac_token, random_token};
+use crate::services::auth::discard_unproven_credentials;
+use crate::services::mail::Email;
+use crate::state::AppState;
+
+const VERIFICATION_TOKEN_TTL_HOURS: i64 = 24;
+/// At most this many links per user within `RESEND_WINDOW_MINUTES`, registration mail included.
+const MAX_LINKS_PER_WINDOW: i64 = 3;
+const RESEND_WINDOW_MINUTES: i64 = 15;
+
+pub struct EmailVerificationService {
+    state: AppState,
+}
+
+impl EmailVerificationService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    /// Issues a fresh single-use token and mails the verification link. No-op if already verified
+    /// or if the account has no email (phone sign-up).
+    pub async fn send_verification(&self, user: &User) -> Result<(), ApiError> {
+        let Some(email) = user.email.as_deref() else {
+            return Ok(());
+        };
+        if user.email_verified_at.is_some() {
+            return Ok(());
+        }
+
+        let token = random_token(32);
+        let token_hash = hmac_token(&self.state.config.one_time_token_secret, &token)?;
+        let expires_at = Utc::now() + Duration::hours(VERIFICATION_TOKEN_TTL_HOURS);
+
+        sqlx::query(
+            "INSERT INTO email_verification_tokens (user_id, token_hash, expires_at) VALUES ($1, $2, $3)",
+        )
+        .bind(user.id)
+        .bind(&token_hash)
+        .bind(expires_at)
+        .execute(&self.state.db.pool)
+        .await?;
+
+        let link = format!(
+            "{}/verify-email?token={}",
+            self.state.config.app_public_url,
+            urlencoding::encode(&token)
+        );
+        self.state
+            .mailer
+            .send(Email {
+                to: email.to_string(),
+                subject: "Confirmez votre adresse email".to_string(),
+                body: format!(
+                    "Bonjour,\n\nConfirmez votre adresse email pour activer votre compte Tikiya :\n{}\n\nCe lien expire dans {} heures.",
+                    link, VERIFICATION_TOKEN_TTL_HOURS
+                ),
+            })
+            .await?;
+
+        tracing::info!(user_id = %user.id, "auth.verify_email.sent");
+        Ok(())
+    }
+
+    /// Sends a new link, at most `MAX_LINKS_PER_WINDOW` per window; beyond that `429` with the
+    /// wait until the oldest link of the window leaves it.
+    pub async fn resend(&self, user_id: Uuid) -> Result<(), ApiError> {
+        let window_start = Utc::now() - Duration::minutes(RESEND_WINDOW_MINUTES);
+        let (recent, oldest) = sqlx::query_as::<_, (i64, Option<DateTime<Utc>>)>(
+            "SELECT COUNT(*), MIN(created_at) FROM email_verification_tokens WHERE user_id = $1 AND created_at > $2",
+        )
+        .bind(user_id)
+        .bind(window_start)
+        .fetch_one(&self.state.db.pool)
+        .await?;
+        if recent >= MAX_LINKS_PER_WINDOW {
+            let retry_after_secs = oldest.map(|t| (t - window_start).num_seconds()).unwrap_or(0).max(1);
+            tracing::warn!(user_id = %user_id, "auth.verify_email.rate_limited");
+            return Err(ApiError::TooManyAttempts { retry_after_secs, captcha_required: false });
+        }
+
+        let user = sqlx::query_as::<_, User>(&format!("SELECT {USER_COLUMNS} FROM users WHERE id = $1"))
+            .bind(user_id)
+            .fetch_optional(&self.state.db.pool)
+            .await?
+            .ok_or(ApiError::Unauthorized)?;
+
+        self.send_verification(&user).await
+    }
+
+    /// Consumes a verification token and marks the owner's email as verified, dropping the
+    /// credentials set up before (see [`discard_unproven_credentials`]).
+    pub async fn verify(&self, token: &str) -> Result<User, ApiError> {
+        let token_hash = hmac_token(&self.state.config.one_time_token_secret, token)?;
+        let mut tx = self.state.db.pool.begin().await?;
+
+        let user_id = sqlx::query_scalar::<_, Uuid>(
+            "UPDATE email_verification_tokens SET consumed_at = NOW() WHERE token_hash = $1 AND consumed_at IS NULL AND expires_at > NOW() RETURNING user_id",
+        )
+        .bind(&token_hash)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| ApiError::Validation("invalid or expired verification token".into()))?;
+
+        // As with magic links and password resets: on a first proof of the address, what whoever
+        // registered the account set up is discarded.
+        let (email, email_verified_at) = sqlx::query_as::<_, (Option<String>, Option<DateTime<Utc>>)>(
+            "SELECT email, email_verified_at FROM users WHERE id = $1 FOR UPDATE",
+        )
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if let (None, Some(email)) = (email_verified_at, email.as_deref()) {
+            discard_unproven_credentials(&mut tx, user_id, email).await?;
+        }
+
+        let user = sqlx::query_as::<_, User>(&format!(
+            "UPDATE users SET email_verified_at = COALESCE(email_verified_at, NOW()) WHERE id = $1 RETURNING {USER_COLUMNS}"
+        ))
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        tracing::info!(user_id = %user.id, "auth.verify_email.success");
+        Ok(user)
+    }
+}
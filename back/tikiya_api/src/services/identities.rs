@@ -0,0 +1,125 @@
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::dto::IdentityResponse;
+use crate::error::ApiError;
+use crate::security::client_info::ClientInfo;
+use crate::services::audit::{self, AuditEvent, Outcome};
+use crate::services::oauth::insert_identity;
+use crate::services::oidc::ProviderUserInfo;
+use crate::state::AppState;
+
+pub struct IdentityService {
+    state: AppState,
+}
+
+impl IdentityService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    pub async fn list(&self, user_id: Uuid) -> Result<Vec<IdentityResponse>, ApiError> {
+        #[derive(sqlx::FromRow)]
+        struct IdentityRow {
+            provider: String,
+            email: Option<String>,
+            created_at: DateTime<Utc>,
+            last_login_at: Option<DateTime<Utc>>,
+        }
+
+        let rows = sqlx::query_as::<_, IdentityRow>(
+            "SELECT provider, email, created_at, last_login_at FROM user_identities WHERE user_id = $1 ORDER BY created_at",
+        )
+        .bind(user_id)
+        .fetch_all(&self.state.db.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| IdentityResponse {
+                provider: row.provider,
+                email: row.email,
+                created_at: row.created_at,
+                last_login_at: row.last_login_at,
+            })
+            .collect())
+    }
+
+    /// Explicitly links a provider identity to the signed-in user, whatever its email.
+    pub async fn link(&self, user_id: Uuid, provider: &str, info: &ProviderUserInfo, client: &ClientInfo) -> Result<(), ApiError> {
+        let mut tx = self.state.db.pool.begin().await?;
+
+        let owner = sqlx::query_scalar::<_, Uuid>(
+            "SELECT user_id FROM user_identities WHERE provider = $1 AND subject = $2",
+        )
+        .bind(provider)
+        .bind(&info.sub)
+        .fetch_optional(&mut *tx)
+        .await?;
+        match owner {
+            Some(owner) if owner == user_id => return Ok(()),
+            Some(_) => {
+                return Err(ApiError::Conflict(format!("this {provider} account is linked to another user")));
+            }
+            None => {}
+        }
+
+        if !insert_identity(&mut tx, user_id, provider, info).await? {
+            return Err(ApiError::Conflict(format!("another {provider} account is already linked")));
+        }
+
+        // The provider vouches for the address: counts as verification when it is the account's own.
+        if info.email_verified {
+            sqlx::query("UPDATE users SET email_verified_at = COALESCE(email_verified_at, NOW()) WHERE id = $1 AND email = $2")
+                .bind(user_id)
+                .bind(&info.email)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let event = AuditEvent::new("auth.identity.linked", Outcome::Success, client)
+            .user(user_id)
+            .details(json!({ "provider": provider }));
+        audit::record(&mut tx, &event).await?;
+        tx.commit().await?;
+        tracing::info!(user_id = %user_id, provider, "auth.identity.linked");
+        Ok(())
+    }
+
+    /// Removes a linked identity, unless it is the account's last way to sign in.
+    pub async fn unlink(&self, user_id: Uuid, provider: &str, client: &ClientInfo) -> Result<(), ApiError> {
+        let mut tx = self.state.db.pool.begin().await?;
+
+        let has_other_method = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM users WHERE id = $1 AND password_hash IS NOT NULL) \
+             OR EXISTS (SELECT 1 FROM user_identities WHERE user_id = $1 AND provider <> $2) \
+             OR EXISTS (SELECT 1 FROM webauthn_credentials WHERE user_id = $1)",
+        )
+        .bind(user_id)
+        .bind(provider)
+        .fetch_one(&mut *tx)
+        .await?;
+        if !has_other_method {
+            return Err(ApiError::Conflict("set a password or add another sign-in method first".into()));
+        }
+
+        let deleted = sqlx::query("DELETE FROM user_identities WHERE user_id = $1 AND provider = $2")
+            .bind(user_id)
+            .bind(provider)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if deleted == 0 {
+            return Err(ApiError::NotFound);
+        }
+
+        let event = AuditEvent::new("auth.identity.unlinked", Outcome::Success, client)
+            .user(user_id)
+            .details(json!({ "provider": provider }));
+        audit::record(&mut tx, &event).await?;
+        tx.commit().await?;
+        tracing::info!(user_id = %user_id, provider, "auth.identity.unlinked");
+        Ok(())
+    }
+}
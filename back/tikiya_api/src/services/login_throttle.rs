@@ -0,0 +1,200 @@
+use std::net::IpAddr;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::config::LoginThrottleConfig;
+use crate::error::ApiError;
+use crate::state::AppState;
+
+const SCOPE_IP_ACCOUNT: &str = "ip_account";
+const SCOPE_IP: &str = "ip";
+
+/// Exponential backoff on password logins, also applied to re-authentication and phone codes.
+/// Each attempt is reserved before the secret is checked. Counting per (IP, email) instead of per account means
+/// a third party cannot lock a victim out; the per-IP counter catches stuffing across accounts.
+/// Unknown emails are counted like known ones.
+pub struct LoginThrottleService {
+    state: AppState,
+}
+
+impl LoginThrottleService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    fn config(&self) -> &LoginThrottleConfig {
+        &self.state.config.login_throttle
+    }
+
+    /// Rejects the attempt with `TooManyAttempts` while either counter is in its backoff period;
+    /// otherwise reserves it, counting it as a failure on both counters up front. Concurrent
+    /// attempts are serialized on the counter rows, so a burst cannot outrun the free attempts;
+    /// `record_success` gives the reservation back.
+    pub async fn check(&self, ip: Option<IpAddr>, email: &str) -> Result<(), ApiError> {
+        let now = Utc::now();
+        let window_start = now - Duration::seconds(self.config().failure_window_secs);
+        // Always locked in this order, so two checks cannot deadlock.
+        let counters = [
+            (SCOPE_IP_ACCOUNT, ip_account_key(ip, email), self.config().free_attempts),
+            (SCOPE_IP, ip_key(ip), self.config().ip_free_attempts),
+        ];
+
+        let mut tx = self.state.db.pool.begin().await?;
+        let mut rows = Vec::with_capacity(counters.len());
+        for (scope, key, _) in &counters {
+            let row = sqlx::query_as::<_, (i32, DateTime<Utc>, Option<DateTime<Utc>>)>(
+                "INSERT INTO login_throttles (scope, key, failures, last_failure_at) VALUES ($1, $2, 0, NOW()) \
+                 ON CONFLICT (scope, key) DO UPDATE SET key = EXCLUDED.key \
+                 RETURNING failures, last_failure_at, blocked_until",
+            )
+            .bind(scope)
+            .bind(key)
+            .fetch_one(&mut *tx)
+            .await?;
+            rows.push(row);
+        }
+
+        let retry_after_secs = rows
+            .iter()
+            .filter_map(|(_, _, until)| *until)
+            .filter(|until| *until > now)
+            .map(|until| (until - now).num_seconds() + 1)
+            .max();
+        if let Some(retry_after_secs) = retry_after_secs {
+            tx.rollback().await?;
+            let failures = rows.iter().map(|(f, _, _)| *f).max().unwrap_or(0);
+            tracing::warn!(ip = ?ip, retry_after_secs, "auth.login.throttled");
+            return Err(ApiError::TooManyAttempts {
+                retry_after_secs,
+                captcha_required: self.captcha_required(failures),
+            });
+        }
+
+        for ((scope, key, free_attempts), (failures, last_failure_at, _)) in counters.iter().zip(rows) {
+            let failures = if last_failure_at < window_start { 1 } else { failures + 1 };
+            let delay = self.backoff_secs(failures, *free_attempts);
+            sqlx::query(
+                "UPDATE login_throttles SET failures = $1, last_failure_at = NOW(), blocked_until = $2 WHERE scope = $3 AND key = $4",
+            )
+            .bind(failures)
+            .bind((delay > 0).then(|| now + Duration::seconds(delay)))
+            .bind(scope)
+            .bind(key)
+            .execute(&mut *tx)
+            .await?;
+            if delay > 0 {
+                tracing::warn!(scope, failures, delay_secs = delay, "auth.login.backoff");
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Rejects with `TooManyAttempts` while either counter is in its backoff period, without
+    /// reserving an attempt. Used before handing out something that leads to a checked attempt
+    /// (an MFA challenge).
+    pub async fn ensure_not_blocked(&self, ip: Option<IpAddr>, email: &str) -> Result<(), ApiError> {
+        let (blocked_until, failures) = sqlx::query_as::<_, (Option<DateTime<Utc>>, Option<i32>)>(
+            "SELECT MAX(blocked_until), MAX(failures) FROM login_throttles WHERE (scope = $1 AND key = $2) OR (scope = $3 AND key = $4)",
+        )
+        .bind(SCOPE_IP_ACCOUNT)
+        .bind(ip_account_key(ip, email))
+        .bind(SCOPE_IP)
+        .bind(ip_key(ip))
+        .fetch_one(&self.state.db.pool)
+        .await?;
+
+        let now = Utc::now();
+        match blocked_until.filter(|until| *until > now) {
+            Some(until) => {
+                tracing::warn!(ip = ?ip, "auth.login.throttled");
+                Err(ApiError::TooManyAttempts {
+                    retry_after_secs: (until - now).num_seconds() + 1,
+                    captcha_required: self.captcha_required(failures.unwrap_or(0)),
+                })
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// The attempt reserved by `check` failed: it is already counted, so this only picks the
+    /// error to answer with.
+    pub async fn record_failure(&self, ip: Option<IpAddr>, email: &str) -> Result<ApiError, ApiError> {
+        let failures = sqlx::query_scalar::<_, Option<i32>>(
+            "SELECT MAX(failures) FROM login_throttles WHERE (scope = $1 AND key = $2) OR (scope = $3 AND key = $4)",
+        )
+        .bind(SCOPE_IP_ACCOUNT)
+        .bind(ip_account_key(ip, email))
+        .bind(SCOPE_IP)
+        .bind(ip_key(ip))
+        .fetch_one(&self.state.db.pool)
+        .await?
+        .unwrap_or(0);
+
+        if self.captcha_required(failures) {
+            Ok(ApiError::CaptchaRequired)
+        } else {
+            Ok(ApiError::Unauthorized)
+        }
+    }
+
+    /// Clears the (IP, email) counter and gives back the attempt `check` reserved on the per-IP
+    /// counter. The rest of the per-IP count is left to expire: a stuffing run that guesses one
+    /// account right must not reset its budget.
+    pub async fn record_success(&self, ip: Option<IpAddr>, email: &str) -> Result<(), ApiError> {
+        let mut tx = self.state.db.pool.begin().await?;
+        sqlx::query("DELETE FROM login_throttles WHERE scope = $1 AND key = $2")
+            .bind(SCOPE_IP_ACCOUNT)
+            .bind(ip_account_key(ip, email))
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            "UPDATE login_throttles SET failures = GREATEST(failures - 1, 0), \
+             blocked_until = CASE WHEN failures - 1 > $3 THEN blocked_until ELSE NULL END \
+             WHERE scope = $1 AND key = $2",
+        )
+        .bind(SCOPE_IP)
+        .bind(ip_key(ip))
+        .bind(self.config().ip_free_attempts)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Clears the (IP, email) counters of an account from every IP (admin unlock). Per-IP
+    /// counters are shared with other accounts and left alone.
+    pub async fn clear_account(&self, email: &str) -> Result<u64, ApiError> {
+        let cleared = sqlx::query("DELETE FROM login_throttles WHERE scope = $1 AND split_part(key, '|', 2) = $2")
+            .bind(SCOPE_IP_ACCOUNT)
+            .bind(email.trim().to_lowercase())
+            .execute(&self.state.db.pool)
+            .await?
+            .rows_affected();
+        Ok(cleared)
+    }
+
+    /// 0 within the free attempts, then base, 2×base, 4×base... capped at the maximum.
+    fn backoff_secs(&self, failures: i32, free_attempts: i32) -> i64 {
+        let config = self.config();
+        let over = failures - free_attempts;
+        if over <= 0 {
+            return 0;
+        }
+        let factor = 1i64 << (over - 1).min(30);
+        config.base_delay_secs.saturating_mul(factor).min(config.max_delay_secs)
+    }
+
+    fn captcha_required(&self, failures: i32) -> bool {
+        let after = self.config().captcha_after;
+        after > 0 && failures >= after
+    }
+}
+
+fn ip_key(ip: Option<IpAddr>) -> String {
+    ip.map(|ip| ip.to_string()).unwrap_or_else(|| "unknown".to_string())
+}
+
+fn ip_account_key(ip: Option<IpAddr>, email: &str) -> String {
+    format!("{}|{}", ip_key(ip), email.trim().to_lowercase())
+}
@@ -0,0 +1,129 @@
+use chrono::{Duration, Utc};
+
+use crate::dto::LoginResponse;
+use crate::error::ApiError;
+use crate::models::{User, USER_COLUMNS};
+use crate::security::client_info::ClientInfo;
+use crate::security::tokens::{hmac_token, random_token};
+use crate::services::auth::{discard_unproven_credentials, AuthService};
+use crate::services::mail::Email;
+use crate::state::AppState;
+
+const MAGIC_LINK_TTL_MINUTES: i64 = 15;
+/// At most this many links per address within `RATE_WINDOW_MINUTES`.
+const MAX_LINKS_PER_WINDOW: i64 = 3;
+const RATE_WINDOW_MINUTES: i64 = 15;
+
+pub struct MagicLinkService {
+    state: AppState,
+}
+
+impl MagicLinkService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    /// Mails a sign-in link to any address, registered or not. Over the per-address limit the
+    /// request is dropped silently: the caller always answers 202.
+    pub async fn send(&self, email: &str) -> Result<(), ApiError> {
+        let recent = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM magic_link_tokens WHERE lower(email) = lower($1) AND created_at > $2",
+        )
+        .bind(email)
+        .bind(Utc::now() - Duration::minutes(RATE_WINDOW_MINUTES))
+        .fetch_one(&self.state.db.pool)
+        .await?;
+        if recent >= MAX_LINKS_PER_WINDOW {
+            tracing::warn!("auth.magic_link.rate_limited");
+            return Ok(());
+        }
+
+        let token = random_token(32);
+        let token_hash = hmac_token(&self.state.config.one_time_token_secret, &token)?;
+        let expires_at = Utc::now() + Duration::minutes(MAGIC_LINK_TTL_MINUTES);
+
+        sqlx::query("INSERT INTO magic_link_tokens (email, token_hash, expires_at) VALUES ($1, $2, $3)")
+            .bind(email)
+            .bind(&token_hash)
+            .bind(expires_at)
+            .execute(&self.state.db.pool)
+            .await?;
+
+        let link = format!(
+            "{}/magic-link?token={}",
+            self.state.config.app_public_url,
+            urlencoding::encode(&token)
+        );
+        self.state
+            .mailer
+            .send(Email {
+                to: email.to_string(),
+                subject: "Votre lien de connexion Tikiya".to_string(),
+                body: format!(
+                    "Bonjour,\n\nPour vous connecter à Tikiya, ouvrez ce lien :\n{}\n\nCe lien est valable une seule fois pendant {} minutes. Si vous n'êtes pas à l'origine de cette demande, ignorez ce message.",
+                    link, MAGIC_LINK_TTL_MINUTES
+                ),
+            })
+            .await?;
+
+        tracing::info!("auth.magic_link.sent");
+        Ok(())
+    }
+
+    /// Consumes the link and signs its owner in, creating the account on first use. Opening the
+    /// link proves ownership of the address, so the email is marked verified; on an account whose
+    /// email was unverified, the credentials set up by whoever registered it are discarded first.
+    pub async fn verify(&self, token: &str, client: &ClientInfo) -> Result<LoginResponse, ApiError> {
+        let token_hash = hmac_token(&self.state.config.one_time_token_secret, token)?;
+        let mut tx = self.state.db.pool.begin().await?;
+
+        let email = sqlx::query_scalar::<_, String>(
+            "UPDATE magic_link_tokens SET consumed_at = NOW() WHERE token_hash = $1 AND consumed_at IS NULL AND expires_at > NOW() RETURNING email",
+        )
+        .bind(&token_hash)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| ApiError::Validation("invalid or expired magic link".into()))?;
+
+        // Other outstanding links for this address are now stale.
+        sqlx::query("UPDATE magic_link_tokens SET consumed_at = NOW() WHERE lower(email) = lower($1) AND consumed_at IS NULL")
+            .bind(&email)
+            .execute(&mut *tx)
+            .await?;
+
+        let existing = sqlx::query_as::<_, User>(&format!(
+            "SELECT {USER_COLUMNS} FROM users WHERE lower(email) = lower($1) FOR UPDATE"
+        ))
+        .bind(&email)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let user = match existing {
+            Some(user) if user.email_verified_at.is_some() => user,
+            Some(user) => {
+                discard_unproven_credentials(&mut tx, user.id, &email).await?;
+                sqlx::query_as::<_, User>(&format!(
+                    "UPDATE users SET email_verified_at = NOW() WHERE id = $1 RETURNING {USER_COLUMNS}"
+                ))
+                .bind(user.id)
+                .fetch_one(&mut *tx)
+                .await?
+            }
+            None => {
+                let user = sqlx::query_as::<_, User>(&format!(
+                    "INSERT INTO users (email, role, email_verified_at) VALUES ($1, 'client', NOW()) RETURNING {USER_COLUMNS}"
+                ))
+                .bind(&email)
+                .fetch_one(&mut *tx)
+                .await?;
+                tracing::info!(user_id = %user.id, "auth.magic_link.user_created");
+                user
+            }
+        };
+
+        tx.commit().await?;
+
+        tracing::info!(user_id = %user.id, "auth.magic_link.verified");
+        AuthService::new(self.state.clone()).complete_login(&user, client, "magic_link", false).await
+    }
+}
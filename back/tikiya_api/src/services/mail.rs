@@ -0,0 +1,74 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use futures_util::future::BoxFuture;
+
+use crate::config::AppConfig;
+use crate::error::ApiError;
+
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Outgoing mail transport. Selected with `MAIL_TRANSPORT` (`log` or `file`).
+pub trait MailSender: Send + Sync {
+    fn send(&self, email: Email) -> BoxFuture<'_, Result<(), ApiError>>;
+}
+
+pub fn from_config(config: &AppConfig) -> Arc<dyn MailSender> {
+    match config.mail_transport.as_str() {
+        "file" => Arc::new(FileMailSender {
+            from: config.mail_from.clone(),
+            dir: PathBuf::from(&config.mail_outbox_dir),
+        }),
+        "log" => Arc::new(LogMailSender),
+        // Refuse to start rather than guess: a misspelt transport must not leave links in the logs.
+        other => panic!("MAIL_TRANSPORT inconnu: '{}' (log ou file)", other),
+    }
+}
+
+/// Development transport: logs that a message was sent, without its body (verification, reset
+/// and sign-in links are bearer tokens). Use the `file` transport to read the messages.
+pub struct LogMailSender;
+
+impl MailSender for LogMailSender {
+    fn send(&self, email: Email) -> BoxFuture<'_, Result<(), ApiError>> {
+        Box::pin(async move {
+            tracing::info!(to = %email.to, subject = %email.subject, body_len = email.body.len(), "mail.log.sent");
+            Ok(())
+        })
+    }
+}
+
+/// Writes one `.eml` file per message into an outbox directory (local dev, tests, relay pickup).
+pub struct FileMailSender {
+    from: String,
+    dir: PathBuf,
+}
+
+impl MailSender for FileMailSender {
+    fn send(&self, email: Email) -> BoxFuture<'_, Result<(), ApiError>> {
+        Box::pin(async move {
+            let path = self
+                .dir
+                .join(format!("{}-{}.eml", chrono::Utc::now().timestamp_millis(), uuid::Uuid::new_v4()));
+            let content = format!(
+                "From: {}\r\nTo: {}\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}\r\n",
+                self.from, email.to, email.subject, email.body
+            );
+            tokio::fs::create_dir_all(&self.dir).await.map_err(|e| {
+                tracing::error!(error = ?e, "mail.file.create_dir_failed");
+                ApiError::Internal
+            })?;
+            tokio::fs::write(&path, content).await.map_err(|e| {
+                tracing::error!(error = ?e, "mail.file.write_failed");
+                ApiError::Internal
+            })?;
+            tracing::info!(to = %email.to, path = %path.display(), "mail.file.sent");
+            Ok(())
+        })
+    }
+}
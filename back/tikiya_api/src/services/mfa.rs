@@ -0,0 +1,442 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::{Duration, Utc};
+use serde_json::json;
+use totp_rs::{Algorithm, Secret, TOTP};
+use uuid::Uuid;
+
+use crate::dto::{AuthResponse, MfaChallengeResponse, MfaCodeRequest, MfaLoginRequest, RecoveryCodesResponse, TotpEnrollmentResponse, UserResponse};
+use crate::error::ApiError;
+use crate::models::{User, USER_COLUMNS};
+use crate::security::client_info::ClientInfo;
+use crate::security::secret_box::SecretBox;
+use crate::security::tokens::{hmac_token, random_token};
+use crate::services::audit::{self, AuditEvent, Outcome};
+use crate::services::auth::AuthService;
+use crate::services::login_throttle::LoginThrottleService;
+use crate::state::AppState;
+
+const TOTP_STEP_SECS: u64 = 30;
+const TOTP_DIGITS: usize = 6;
+const TOTP_SECRET_BYTES: usize = 20;
+const RECOVERY_CODE_COUNT: usize = 10;
+const CHALLENGE_TTL_MINUTES: i64 = 5;
+const CHALLENGE_MAX_ATTEMPTS: i32 = 5;
+
+pub struct MfaService {
+    state: AppState,
+}
+
+impl MfaService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    pub async fn totp_enabled(&self, user_id: Uuid) -> Result<bool, ApiError> {
+        let enabled = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM user_mfa_totp WHERE user_id = $1 AND confirmed_at IS NOT NULL)",
+        )
+        .bind(user_id)
+        .fetch_one(&self.state.db.pool)
+        .await?;
+        Ok(enabled)
+    }
+
+    /// Starts (or restarts) enrollment with a fresh secret. Not active until confirmed with a code.
+    pub async fn start_totp_enrollment(&self, user_id: Uuid, email: &str) -> Result<TotpEnrollmentResponse, ApiError> {
+        if self.totp_enabled(user_id).await? {
+            return Err(ApiError::Conflict("two-factor authentication is already enabled".into()));
+        }
+
+        let mut bytes = [0u8; TOTP_SECRET_BYTES];
+        OsRng.fill_bytes(&mut bytes);
+        let secret = match Secret::Raw(bytes.to_vec()).to_encoded() {
+            Secret::Encoded(s) => s,
+            Secret::Raw(_) => return Err(ApiError::Internal),
+        };
+        let totp = self.totp(&secret, email)?;
+        let sealed = self.secret_box().seal(&secret, user_id.as_bytes())?;
+
+        sqlx::query(
+            "INSERT INTO user_mfa_totp (user_id, secret) VALUES ($1, $2) ON CONFLICT (user_id) DO UPDATE SET secret = EXCLUDED.secret, confirmed_at = NULL, last_used_step = NULL, created_at = NOW()",
+        )
+        .bind(user_id)
+        .bind(&sealed)
+        .execute(&self.state.db.pool)
+        .await?;
+
+        tracing::info!(user_id = %user_id, "auth.mfa.totp_enrollment_started");
+
+        Ok(TotpEnrollmentResponse {
+            otpauth_uri: totp.get_url(),
+            secret,
+        })
+    }
+
+    /// Activates TOTP once the user proves the authenticator works, and hands out recovery codes.
+    /// The calling session is marked as MFA-verified.
+    pub async fn confirm_totp_enrollment(
+        &self,
+        user_id: Uuid,
+        email: &str,
+        session_id: Option<Uuid>,
+        code: &str,
+        client: &ClientInfo,
+    ) -> Result<RecoveryCodesResponse, ApiError> {
+        let sealed = sqlx::query_scalar::<_, String>(
+            "SELECT secret FROM user_mfa_totp WHERE user_id = $1 AND confirmed_at IS NULL",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.state.db.pool)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+        let secret = self.open_secret(user_id, &sealed)?;
+
+        let step = self
+            .matching_step(&secret, email, code)?
+            .ok_or_else(|| ApiError::Validation("invalid code".into()))?;
+
+        let recovery_codes = generate_recovery_codes();
+        let mut tx = self.state.db.pool.begin().await?;
+
+        sqlx::query("UPDATE user_mfa_totp SET confirmed_at = NOW(), last_used_step = $2 WHERE user_id = $1")
+            .bind(user_id)
+            .bind(step as i64)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM mfa_recovery_codes WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        for code in &recovery_codes {
+            sqlx::query("INSERT INTO mfa_recovery_codes (user_id, code_hash) VALUES ($1, $2)")
+                .bind(user_id)
+                .bind(self.hash_recovery_code(code)?)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        if let Some(session_id) = session_id {
+            sqlx::query("UPDATE sessions SET mfa = TRUE WHERE id = $1 AND user_id = $2")
+                .bind(session_id)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let event = AuditEvent::new("auth.mfa.totp_enabled", Outcome::Success, client).user(user_id);
+        audit::record(&mut tx, &event).await?;
+        tx.commit().await?;
+
+        tracing::info!(user_id = %user_id, "auth.mfa.totp_enabled");
+        Ok(RecoveryCodesResponse { recovery_codes })
+    }
+
+    /// Turns TOTP off; requires a valid code or recovery code.
+    pub async fn disable_totp(
+        &self,
+        user_id: Uuid,
+        email: &str,
+        proof: &MfaCodeRequest,
+        client: &ClientInfo,
+    ) -> Result<(), ApiError> {
+        if !self.check_second_factor(user_id, email, proof, client).await? {
+            return Err(ApiError::Validation("invalid code".into()));
+        }
+
+        let mut tx = self.state.db.pool.begin().await?;
+        sqlx::query("DELETE FROM user_mfa_totp WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM mfa_recovery_codes WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        let event = AuditEvent::new("auth.mfa.totp_disabled", Outcome::Success, client).user(user_id);
+        audit::record(&mut tx, &event).await?;
+        tx.commit().await?;
+
+        tracing::info!(user_id = %user_id, "auth.mfa.totp_disabled");
+        Ok(())
+    }
+
+    /// Replaces all recovery codes; requires a valid code or recovery code.
+    pub async fn regenerate_recovery_codes(
+        &self,
+        user_id: Uuid,
+        email: &str,
+        proof: &MfaCodeRequest,
+        client: &ClientInfo,
+    ) -> Result<RecoveryCodesResponse, ApiError> {
+        if !self.check_second_factor(user_id, email, proof, client).await? {
+            return Err(ApiError::Validation("invalid code".into()));
+        }
+
+        let recovery_codes = generate_recovery_codes();
+        let mut tx = self.state.db.pool.begin().await?;
+        sqlx::query("DELETE FROM mfa_recovery_codes WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        for code in &recovery_codes {
+            sqlx::query("INSERT INTO mfa_recovery_codes (user_id, code_hash) VALUES ($1, $2)")
+                .bind(user_id)
+                .bind(self.hash_recovery_code(code)?)
+                .execute(&mut *tx)
+                .await?;
+        }
+        let event = AuditEvent::new("auth.mfa.recovery_codes_regenerated", Outcome::Success, client).user(user_id);
+        audit::record(&mut tx, &event).await?;
+        tx.commit().await?;
+
+        tracing::info!(user_id = %user_id, "auth.mfa.recovery_codes_regenerated");
+        Ok(RecoveryCodesResponse { recovery_codes })
+    }
+
+    /// Second step of a login: the challenge is single-use and allows a few wrong codes.
+    pub async fn create_challenge(&self, user_id: Uuid) -> Result<MfaChallengeResponse, ApiError> {
+        let token = random_token(32);
+        let token_hash = hmac_token(&self.state.config.one_time_token_secret, &token)?;
+        let expires_at = Utc::now() + Duration::minutes(CHALLENGE_TTL_MINUTES);
+
+        sqlx::query("INSERT INTO mfa_challenges (user_id, token_hash, expires_at) VALUES ($1, $2, $3)")
+            .bind(user_id)
+            .bind(&token_hash)
+            .bind(expires_at)
+            .execute(&self.state.db.pool)
+            .await?;
+
+        Ok(MfaChallengeResponse {
+            mfa_required: true,
+            challenge_token: token,
+            expires_in: CHALLENGE_TTL_MINUTES * 60,
+        })
+    }
+
+    pub async fn complete_login(&self, payload: MfaLoginRequest, client: &ClientInfo) -> Result<AuthResponse, ApiError> {
+        let token_hash = hmac_token(&self.state.config.one_time_token_secret, &payload.challenge_token)?;
+
+        let (challenge_id, user_id) = sqlx::query_as::<_, (Uuid, Uuid)>(
+            "UPDATE mfa_challenges SET attempts = attempts + 1 WHERE token_hash = $1 AND consumed_at IS NULL AND expires_at > NOW() AND attempts < $2 RETURNING id, user_id",
+        )
+        .bind(&token_hash)
+        .bind(CHALLENGE_MAX_ATTEMPTS)
+        .fetch_optional(&self.state.db.pool)
+        .await?
+        .ok_or(ApiError::Unauthorized)?;
+
+        let user = sqlx::query_as::<_, User>(&format!("SELECT {USER_COLUMNS} FROM users WHERE id = $1"))
+            .bind(user_id)
+            .fetch_one(&self.state.db.pool)
+            .await?;
+
+        let account_name = user.email.clone().unwrap_or_else(|| user.id.to_string());
+        if !self.check_second_factor(user.id, &account_name, &payload.proof, client).await? {
+            tracing::warn!(user_id = %user.id, "auth.login.mfa_invalid_code");
+            let event = AuditEvent::new("auth.login.mfa", Outcome::Failure, client)
+                .subject(Some(user.id))
+                .details(json!({ "reason": "invalid_code" }));
+            audit::record_detached(&self.state, event).await;
+            return Err(LoginThrottleService::new(self.state.clone())
+                .record_failure(client.ip, &second_factor_throttle_key(user.id))
+                .await?);
+        }
+
+        let consumed = sqlx::query("UPDATE mfa_challenges SET consumed_at = NOW() WHERE id = $1 AND consumed_at IS NULL")
+            .bind(challenge_id)
+            .execute(&self.state.db.pool)
+            .await?
+            .rows_affected();
+        if consumed == 0 {
+            return Err(ApiError::Unauthorized);
+        }
+
+        let auth = AuthService::new(self.state.clone());
+        let tokens = auth.issue_tokens(&user, client, true).await?;
+
+        tracing::info!(user_id = %user.id, email = ?user.email, "auth.login.mfa_success");
+        let event = AuditEvent::new("auth.login.mfa", Outcome::Success, client)
+            .user(user.id)
+            .details(json!({ "method": if payload.proof.recovery_code.is_some() { "recovery_code" } else { "totp" } }));
+        audit::record_detached(&self.state, event).await;
+
+        Ok(AuthResponse {
+            user: UserResponse::from(&user),
+            tokens,
+        })
+    }
+
+    /// [`verify_second_factor`](Self::verify_second_factor) behind the login backoff, counted per
+    /// (IP, user id): a challenge caps its own attempts, but fresh challenges and authenticated
+    /// callers would otherwise guess without limit.
+    pub(crate) async fn check_second_factor(
+        &self,
+        user_id: Uuid,
+        email: &str,
+        proof: &MfaCodeRequest,
+        client: &ClientInfo,
+    ) -> Result<bool, ApiError> {
+        let throttle = LoginThrottleService::new(self.state.clone());
+        let key = second_factor_throttle_key(user_id);
+        throttle.check(client.ip, &key).await?;
+        if !self.verify_second_factor(user_id, email, proof).await? {
+            return Ok(false);
+        }
+        throttle.record_success(client.ip, &key).await?;
+        Ok(true)
+    }
+
+    /// Checks a TOTP code (each time step usable once) or burns a recovery code.
+    async fn verify_second_factor(&self, user_id: Uuid, email: &str, proof: &MfaCodeRequest) -> Result<bool, ApiError> {
+        if let Some(code) = proof.code.as_deref() {
+            let Some(sealed) = sqlx::query_scalar::<_, String>(
+                "SELECT secret FROM user_mfa_totp WHERE user_id = $1 AND confirmed_at IS NOT NULL",
+            )
+            .bind(user_id)
+            .fetch_optional(&self.state.db.pool)
+            .await?
+            else {
+                return Ok(false);
+            };
+            let secret = self.open_secret(user_id, &sealed)?;
+
+            let Some(step) = self.matching_step(&secret, email, code)? else {
+                return Ok(false);
+            };
+
+            let accepted = sqlx::query(
+                "UPDATE user_mfa_totp SET last_used_step = $2 WHERE user_id = $1 AND (last_used_step IS NULL OR last_used_step < $2)",
+            )
+            .bind(user_id)
+            .bind(step as i64)
+            .execute(&self.state.db.pool)
+            .await?
+            .rows_affected();
+            return Ok(accepted == 1);
+        }
+
+        if let Some(recovery_code) = proof.recovery_code.as_deref() {
+            let used = sqlx::query(
+                "UPDATE mfa_recovery_codes SET used_at = NOW() WHERE id = (SELECT id FROM mfa_recovery_codes WHERE user_id = $1 AND code_hash = ANY($2) AND used_at IS NULL LIMIT 1)",
+            )
+            .bind(user_id)
+            .bind(self.recovery_code_hashes(recovery_code)?)
+            .execute(&self.state.db.pool)
+            .await?
+            .rows_affected();
+            if used == 1 {
+                tracing::info!(user_id = %user_id, "auth.mfa.recovery_code_used");
+            }
+            return Ok(used == 1);
+        }
+
+        Ok(false)
+    }
+
+    /// Encrypts TOTP secrets still stored in plaintext (written before encryption at rest).
+    /// Runs at startup; returns how many rows were sealed.
+    pub async fn seal_plaintext_secrets(&self) -> Result<u64, ApiError> {
+        let rows = sqlx::query_as::<_, (Uuid, String)>("SELECT user_id, secret FROM user_mfa_totp WHERE secret NOT LIKE 'v1:%'")
+            .fetch_all(&self.state.db.pool)
+            .await?;
+        let secret_box = self.secret_box();
+        let mut sealed = 0;
+        for (user_id, secret) in rows {
+            sealed += sqlx::query("UPDATE user_mfa_totp SET secret = $3 WHERE user_id = $1 AND secret = $2")
+                .bind(user_id)
+                .bind(&secret)
+                .bind(secret_box.seal(&secret, user_id.as_bytes())?)
+                .execute(&self.state.db.pool)
+                .await?
+                .rows_affected();
+        }
+        if sealed > 0 {
+            tracing::info!(count = sealed, "auth.mfa.plaintext_secrets_sealed");
+        }
+        Ok(sealed)
+    }
+
+    fn secret_box(&self) -> SecretBox {
+        SecretBox::new(&self.state.config.mfa_encryption_key)
+    }
+
+    fn open_secret(&self, user_id: Uuid, stored: &str) -> Result<String, ApiError> {
+        if !SecretBox::is_sealed(stored) {
+            // Written by an instance that predates encryption; sealed at the next startup.
+            tracing::warn!(user_id = %user_id, "auth.mfa.plaintext_secret");
+            return Ok(stored.to_string());
+        }
+        self.secret_box().open(stored, user_id.as_bytes())
+    }
+
+    fn totp(&self, secret: &str, email: &str) -> Result<TOTP, ApiError> {
+        let bytes = Secret::Encoded(secret.to_string())
+            .to_bytes()
+            .map_err(|_| ApiError::Internal)?;
+        TOTP::new(
+            Algorithm::SHA1,
+            TOTP_DIGITS,
+            1,
+            TOTP_STEP_SECS,
+            bytes,
+            Some(self.state.config.mfa_issuer.clone()),
+            email.to_string(),
+        )
+        .map_err(|err| {
+            tracing::error!(?err, "auth.mfa.totp_init_failed");
+            ApiError::Internal
+        })
+    }
+
+    /// Time step (previous, current or next) whose code matches, if any.
+    fn matching_step(&self, secret: &str, email: &str, code: &str) -> Result<Option<u64>, ApiError> {
+        let totp = self.totp(secret, email)?;
+        let code = code.trim();
+        let current = Utc::now().timestamp() as u64 / TOTP_STEP_SECS;
+        Ok([current - 1, current, current + 1]
+            .into_iter()
+            .find(|step| totp.check(code, step * TOTP_STEP_SECS)))
+    }
+
+    fn hash_recovery_code(&self, code: &str) -> Result<String, ApiError> {
+        hmac_token(&self.state.config.one_time_token_secret, &normalize_recovery_code(code))
+    }
+
+    /// Hashes a presented recovery code may be stored under: the current key, and `JWT_SECRET`
+    /// for codes issued before the one-time-token key was derived from it.
+    fn recovery_code_hashes(&self, code: &str) -> Result<Vec<String>, ApiError> {
+        let mut hashes = vec![self.hash_recovery_code(code)?];
+        if let Some(legacy) = self.state.config.one_time_token_legacy_secret.as_deref() {
+            hashes.push(hmac_token(legacy, &normalize_recovery_code(code))?);
+        }
+        Ok(hashes)
+    }
+}
+
+/// Login-throttle key for second-factor attempts, whatever the route.
+pub(crate) fn second_factor_throttle_key(user_id: Uuid) -> String {
+    user_id.to_string()
+}
+
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn generate_recovery_codes() -> Vec<String> {
+    const ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let mut bytes = [0u8; 10];
+            OsRng.fill_bytes(&mut bytes);
+            let chars: String = bytes
+                .iter()
+                .map(|b| ALPHABET[*b as usize % ALPHABET.len()] as char)
+                .collect();
+            format!("{}-{}", &chars[..5], &chars[5..])
+        })
+        .collect()
+}
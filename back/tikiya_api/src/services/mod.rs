@@ -1,2 +1,22 @@
+pub mod account;
+pub mod admin_users;
+pub mod audit;
 pub mod auth;
+pub mod cleanup;
+pub mod email_verification;
+pub mod identities;
+pub mod login_throttle;
+pub mod magic_link;
+pub mod mail;
+pub mod mfa;
 pub mod oauth;
+pub mod oidc;
+pub mod organizer;
+pub mod pagination;
+pub mod password_reset;
+pub mod phone_auth;
+pub mod profile;
+pub mod reauth;
+pub mod sessions;
+pub mod sms;
+pub mod webauthn;
@@ -1,185 +1,244 @@
-use axum::http::Uri;
-use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
 
-use crate::dto::{AuthResponse, UserResponse};
+use crate::dto::OAuthCallbackResponse;
 use crate::error::ApiError;
-use crate::models::User;
-use crate::services::auth::AuthService;
+use crate::models::{User, USER_COLUMNS};
+use crate::security::client_info::ClientInfo;
+use crate::security::oauth_state;
+use crate::security::tokens::random_token;
+use crate::services::audit::{self, AuditEvent, Outcome};
+use crate::services::auth::{ensure_can_authenticate, ensure_can_sign_in, AuthService};
+use crate::services::oidc::ProviderUserInfo;
 use crate::state::AppState;
 
-const GOOGLE_AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
-const GOOGLE_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
-const GOOGLE_USERINFO_URL: &str = "https://openidconnect.googleapis.com/v1/userinfo";
-
-#[derive(Debug, Deserialize)]
-struct GoogleTokenResponse {
-    access_token: String,
-    _expires_in: i64,
-    _token_type: String,
-    _id_token: Option<String>,
-    _refresh_token: Option<String>,
-}
-
-#[derive(Debug, Deserialize)]
-pub struct GoogleUserInfo {
-    pub sub: String,
-    pub email: String,
-    pub _email_verified: bool,
-    pub _given_name: Option<String>,
-    pub _family_name: Option<String>,
-}
+/// Unfinished authorizations one client IP may hold at once; each one is a row until it expires.
+const MAX_PENDING_STATES_PER_IP: i64 = 20;
 
 pub struct OAuthService {
     state: AppState,
-    client: reqwest::Client,
 }
 
 impl OAuthService {
     pub fn new(state: AppState) -> Self {
-        let client = reqwest::Client::builder()
-            .user_agent("tikiya-api/1.0")
-            .connect_timeout(Duration::from_secs(5))
-            .timeout(Duration::from_secs(10))
-            .build()
-            .unwrap_or_else(|e| {
-                tracing::error!(error = ?e, "oauth.reqwest_client_build_failed");
-                reqwest::Client::new()
-            });
-        Self { state, client }
+        Self { state }
     }
 
-    pub fn google_auth_url(&self, state_str: &str, code_challenge: Option<&str>, code_challenge_method: Option<&str>) -> Result<Uri, ApiError> {
-        let mut url = format!(
-            "{}?client_id={}&redirect_uri={}&response_type=code&scope=openid%20email%20profile&state={}&access_type=offline&prompt=consent",
-            GOOGLE_AUTH_URL,
-            urlencoding::encode(&self.state.config.google_client_id),
-            urlencoding::encode(&self.state.config.google_redirect_uri),
-            urlencoding::encode(state_str)
-        );
-        if let Some(chal) = code_challenge {
-            let method = code_challenge_method.unwrap_or("S256");
-            url.push_str(&format!("&code_challenge={}&code_challenge_method={}", urlencoding::encode(chal), method));
+    /// Starts an authorization: records a single-use state holding the PKCE verifier, the OIDC
+    /// nonce and the caller's `client_state`, and returns the provider's consent URL. A client IP
+    /// holding `MAX_PENDING_STATES_PER_IP` unfinished authorizations must wait for one to expire.
+    pub async fn auth_url(
+        &self,
+        provider: &str,
+        client_state: Option<&str>,
+        client: &ClientInfo,
+    ) -> Result<String, ApiError> {
+        let oidc = self.state.oidc.get(provider)?;
+        let ip = client.ip_string();
+        let (pending, oldest_expiry) = sqlx::query_as::<_, (i64, Option<DateTime<Utc>>)>(
+            "SELECT COUNT(*), MIN(expires_at) FROM oauth_states \
+             WHERE ip IS NOT DISTINCT FROM $1 AND consumed_at IS NULL AND expires_at > NOW()",
+        )
+        .bind(&ip)
+        .fetch_one(&self.state.db.pool)
+        .await?;
+        if pending >= MAX_PENDING_STATES_PER_IP {
+            tracing::warn!(provider, ip = ?client.ip, pending, "oauth.start.rate_limited");
+            let retry_after_secs = oldest_expiry
+                .map_or(oauth_state::STATE_TTL_SECS, |at| (at - Utc::now()).num_seconds() + 1);
+            return Err(ApiError::TooManyAttempts {
+                retry_after_secs,
+                captcha_required: false,
+            });
         }
-        url.parse::<Uri>().map_err(|_| ApiError::Internal)
-    }
 
-    pub async fn google_callback(&self, code: &str, code_verifier: Option<&str>) -> Result<AuthResponse, ApiError> {
-        let token = self.exchange_code_for_token(code, code_verifier).await?;
-        let userinfo = self.fetch_google_userinfo(&token.access_token).await?;
+        let (state_str, state_nonce) = oauth_state::mint_state(&self.state.config.oauth_state_secret)?;
+        let code_verifier = random_token(32);
+        let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+        let oidc_nonce = random_token(16);
 
-        let user = self.upsert_oauth_user(&userinfo).await?;
-
-        let auth = AuthService::new(self.state.clone());
-        let tokens = auth.issue_tokens(&user).await?;
+        sqlx::query(
+            "INSERT INTO oauth_states (nonce, provider, code_verifier, oidc_nonce, client_state, ip, expires_at) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(&state_nonce)
+        .bind(provider)
+        .bind(&code_verifier)
+        .bind(&oidc_nonce)
+        .bind(client_state)
+        .bind(&ip)
+        .bind(Utc::now() + Duration::seconds(oauth_state::STATE_TTL_SECS))
+        .execute(&self.state.db.pool)
+        .await?;
 
-        Ok(AuthResponse { user: UserResponse::from(&user), tokens })
+        oidc.authorization_url(&state_str, &oidc_nonce, &code_challenge).await
     }
 
-    async fn exchange_code_for_token(&self, code: &str, code_verifier: Option<&str>) -> Result<GoogleTokenResponse, ApiError> {
-        #[derive(Serialize)]
-        struct Body<'a> {
-            code: &'a str,
-            client_id: &'a str,
-            client_secret: &'a str,
-            redirect_uri: &'a str,
-            grant_type: &'a str,
-            #[serde(skip_serializing_if = "Option::is_none")]
-            code_verifier: Option<&'a str>,
-        }
-        let body = Body {
-            code,
-            client_id: &self.state.config.google_client_id,
-            client_secret: &self.state.config.google_client_secret,
-            redirect_uri: &self.state.config.google_redirect_uri,
-            grant_type: "authorization_code",
-            code_verifier,
-        };
-
-        let res = self
-            .client
-            .post(GOOGLE_TOKEN_URL)
-            .form(&body)
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::error!(error = ?e, "google.token.request_failed");
-                ApiError::Internal
-            })?;
-
-        if !res.status().is_success() {
-            let status = res.status();
-            let text = res.text().await.unwrap_or_default();
-            tracing::warn!(status = %status, body = %text, "google.token.exchange_failed");
-            return Err(ApiError::Unauthorized);
+    /// Completes an authorization. The state is consumed first, so a replayed callback fails even
+    /// when the provider would accept the code again.
+    pub async fn callback(
+        &self,
+        provider: &str,
+        code: &str,
+        state_str: &str,
+        client: &ClientInfo,
+    ) -> Result<OAuthCallbackResponse, ApiError> {
+        let oidc = self.state.oidc.get(provider)?;
+        let state_nonce = oauth_state::verify_state(&self.state.config.oauth_state_secret, state_str)?;
+
+        #[derive(sqlx::FromRow)]
+        struct PendingState {
+            code_verifier: String,
+            oidc_nonce: String,
+            client_state: Option<String>,
         }
 
-        res.json::<GoogleTokenResponse>().await.map_err(|e| {
-            tracing::error!(error = ?e, "google.token.parse_failed");
-            ApiError::Internal
-        })
-    }
+        let pending = sqlx::query_as::<_, PendingState>(
+            "UPDATE oauth_states SET consumed_at = NOW() WHERE nonce = $1 AND provider = $2 AND consumed_at IS NULL AND expires_at > NOW() RETURNING code_verifier, oidc_nonce, client_state",
+        )
+        .bind(&state_nonce)
+        .bind(provider)
+        .fetch_optional(&self.state.db.pool)
+        .await?
+        .ok_or_else(|| {
+            tracing::warn!(provider, "oauth.state_replayed_or_unknown");
+            ApiError::Unauthorized
+        })?;
+
+        let userinfo = oidc
+            .user_from_code(code, &pending.code_verifier, Some(&pending.oidc_nonce))
+            .await?;
+        require_email(provider, &userinfo)?;
 
-    async fn fetch_google_userinfo(&self, access_token: &str) -> Result<GoogleUserInfo, ApiError> {
-        let res = self
-            .client
-            .get(GOOGLE_USERINFO_URL)
-            .bearer_auth(access_token)
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::error!(error = ?e, "google.userinfo.request_failed");
-                ApiError::Internal
-            })?;
-
-        if !res.status().is_success() {
-            let status = res.status();
-            let text = res.text().await.unwrap_or_default();
-            tracing::warn!(status = %status, body = %text, "google.userinfo_failed");
-            return Err(ApiError::Unauthorized);
-        }
+        let user = self.upsert_oauth_user(provider, &userinfo, client).await?;
 
-        res.json::<GoogleUserInfo>().await.map_err(|e| {
-            tracing::error!(error = ?e, "google.userinfo.parse_failed");
-            ApiError::Internal
+        let auth = AuthService::new(self.state.clone());
+        Ok(OAuthCallbackResponse {
+            login: auth.complete_login(&user, client, &format!("oauth:{provider}"), false).await?,
+            client_state: pending.client_state,
         })
     }
 
-    pub async fn upsert_oauth_user(&self, info: &GoogleUserInfo) -> Result<User, ApiError> {
-        // Try existing by provider+subject
-        if let Some(existing) = sqlx::query_as::<_, User>(
-            "SELECT id, email, password_hash, role, oauth_provider, oauth_subject, created_at FROM users WHERE oauth_provider = 'google' AND oauth_subject = $1"
+    /// Validates an ID token obtained by a native SDK (mobile sign-in, identity linking) and
+    /// returns its profile.
+    pub async fn verify_id_token(&self, provider: &str, id_token: &str, nonce: Option<&str>) -> Result<ProviderUserInfo, ApiError> {
+        let oidc = self.state.oidc.get(provider)?;
+        let info = oidc.verify_id_token(id_token, nonce).await?;
+        require_email(provider, &info)?;
+        Ok(info)
+    }
+
+    /// Resolves the local account for an external identity. A known identity signs in directly.
+    /// An unknown identity whose email matches an existing account is linked only when both the
+    /// provider and the account have verified that address; otherwise the user has to sign in
+    /// and link it explicitly (`POST /me/identities/{provider}`). No account is created from an
+    /// address the provider has not verified (Microsoft and Facebook never assert it): whoever
+    /// proves the address later would land in an account the provider identity still opens.
+    pub async fn upsert_oauth_user(&self, provider: &str, info: &ProviderUserInfo, client: &ClientInfo) -> Result<User, ApiError> {
+        // Only trust the address as verified when the provider says so.
+        let verified_at = info.email_verified.then(chrono::Utc::now);
+        let mut tx = self.state.db.pool.begin().await?;
+
+        let linked = sqlx::query_scalar::<_, Uuid>(
+            "UPDATE user_identities SET email = $3, email_verified = $4, last_login_at = NOW() WHERE provider = $1 AND subject = $2 RETURNING user_id",
         )
+        .bind(provider)
         .bind(&info.sub)
-        .fetch_optional(&self.state.db.pool)
-        .await? {
-            return Ok(existing);
+        .bind(&info.email)
+        .bind(info.email_verified)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if let Some(user_id) = linked {
+            let user = sqlx::query_as::<_, User>(
+                &format!("UPDATE users SET email_verified_at = CASE WHEN email = $3 THEN COALESCE(email_verified_at, $2) ELSE email_verified_at END WHERE id = $1 RETURNING {USER_COLUMNS}")
+            )
+            .bind(user_id)
+            .bind(verified_at)
+            .bind(&info.email)
+            .fetch_one(&mut *tx)
+            .await?;
+            ensure_can_authenticate(&user)?;
+            tx.commit().await?;
+            return Ok(user);
         }
 
-        if let Some(existing_by_email) = sqlx::query_as::<_, User>(
-            "SELECT id, email, password_hash, role, oauth_provider, oauth_subject, created_at FROM users WHERE email = $1"
+        if let Some(existing) = sqlx::query_as::<_, User>(
+            &format!("SELECT {USER_COLUMNS} FROM users WHERE email = $1 FOR UPDATE")
         )
         .bind(&info.email)
-        .fetch_optional(&self.state.db.pool)
+        .fetch_optional(&mut *tx)
         .await? {
-            let updated = sqlx::query_as::<_, User>(
-                "UPDATE users SET oauth_provider = 'google', oauth_subject = $1 WHERE id = $2 RETURNING id, email, password_hash, role, oauth_provider, oauth_subject, created_at"
-            )
-            .bind(&info.sub)
-            .bind(existing_by_email.id)
-            .fetch_one(&self.state.db.pool)
-            .await?;
-            return Ok(updated);
+            if !info.email_verified || existing.email_verified_at.is_none() {
+                tracing::warn!(user_id = %existing.id, provider, "auth.oauth.link_required");
+                return Err(ApiError::Conflict(
+                    "an account already uses this email: sign in to it, then link this provider from your profile".into(),
+                ));
+            }
+            // Checked before linking: a blocked account must not gain a new way in.
+            ensure_can_sign_in(&existing)?;
+            if !insert_identity(&mut tx, existing.id, provider, info).await? {
+                return Err(ApiError::Conflict(format!("this account is already linked to another {provider} identity")));
+            }
+            let event = AuditEvent::new("auth.identity.linked", Outcome::Success, client)
+                .user(existing.id)
+                .details(json!({ "provider": provider, "automatic": true }));
+            audit::record(&mut tx, &event).await?;
+            tx.commit().await?;
+            tracing::info!(user_id = %existing.id, provider, "auth.oauth.identity_auto_linked");
+            return Ok(existing);
         }
 
+        if !info.email_verified {
+            tracing::warn!(provider, "auth.oauth.unverified_email_signup_refused");
+            return Err(ApiError::Validation(format!(
+                "{provider} has not verified this email address: sign up with it, then link {provider} from your profile"
+            )));
+        }
         let created = sqlx::query_as::<_, User>(
-            "INSERT INTO users (email, oauth_provider, oauth_subject, role) VALUES ($1, 'google', $2, 'client') RETURNING id, email, password_hash, role, oauth_provider, oauth_subject, created_at"
+            &format!("INSERT INTO users (email, role, first_name, last_name, email_verified_at) VALUES ($1, 'client', $2, $3, $4) RETURNING {USER_COLUMNS}")
         )
         .bind(&info.email)
-        .bind(&info.sub)
-        .fetch_one(&self.state.db.pool)
+        .bind(&info.given_name)
+        .bind(&info.family_name)
+        .bind(Utc::now())
+        .fetch_one(&mut *tx)
         .await?;
+        insert_identity(&mut tx, created.id, provider, info).await?;
+        tx.commit().await?;
 
         Ok(created)
     }
 }
+
+/// Links an identity to `user_id`. `false` when it is already linked (to this or another user),
+/// or the user already has an identity for this provider.
+pub(crate) async fn insert_identity(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: Uuid,
+    provider: &str,
+    info: &ProviderUserInfo,
+) -> Result<bool, ApiError> {
+    let inserted = sqlx::query(
+        "INSERT INTO user_identities (user_id, provider, subject, email, email_verified, last_login_at) VALUES ($1, $2, $3, $4, $5, NOW()) ON CONFLICT DO NOTHING",
+    )
+    .bind(user_id)
+    .bind(provider)
+    .bind(&info.sub)
+    .bind(&info.email)
+    .bind(info.email_verified)
+    .execute(&mut **tx)
+    .await?
+    .rows_affected();
+    Ok(inserted == 1)
+}
+
+fn require_email(provider: &str, info: &ProviderUserInfo) -> Result<(), ApiError> {
+    if info.email.trim().is_empty() {
+        tracing::warn!(provider, "oauth.missing_email");
+        return Err(ApiError::Unauthorized);
+    }
+    Ok(())
+}
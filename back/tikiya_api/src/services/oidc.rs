@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Deserializer, Serialize};
+use tokio::sync::OnceCell;
+
+use crate::config::{AppConfig, OidcProviderConfig};
+use crate::error::ApiError;
+use crate::security::remote_jwks::RemoteJwks;
+
+/// Configured identity providers, by name.
+pub struct OidcRegistry {
+    providers: HashMap<String, Arc<OidcProvider>>,
+}
+
+impl OidcRegistry {
+    pub fn from_config(config: &AppConfig, http: reqwest::Client) -> Self {
+        let providers = config
+            .oidc_providers
+            .iter()
+            .map(|provider| {
+                (
+                    provider.name.clone(),
+                    Arc::new(OidcProvider {
+                        config: provider.clone(),
+                        http: http.clone(),
+                        metadata: OnceCell::new(),
+                    }),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+
+        tracing::info!(providers = ?providers.keys().collect::<Vec<_>>(), "oidc.providers.loaded");
+        Self { providers }
+    }
+
+    pub fn get(&self, name: &str) -> Result<Arc<OidcProvider>, ApiError> {
+        self.providers.get(name).cloned().ok_or(ApiError::NotFound)
+    }
+}
+
+/// Profile of the signed-in user, from a verified ID token or the userinfo endpoint.
+#[derive(Debug, Deserialize)]
+pub struct ProviderUserInfo {
+    #[serde(alias = "id")]
+    pub sub: String,
+    #[serde(default)]
+    pub email: String,
+    #[serde(default, deserialize_with = "bool_or_string")]
+    pub email_verified: bool,
+    #[serde(alias = "first_name")]
+    pub given_name: Option<String>,
+    #[serde(alias = "last_name")]
+    pub family_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub id_token: Option<String>,
+}
+
+/// Endpoints resolved from the discovery document and the configured overrides.
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    userinfo_endpoint: Option<String>,
+    jwks: Option<RemoteJwks>,
+}
+
+pub struct OidcProvider {
+    pub config: OidcProviderConfig,
+    http: reqwest::Client,
+    /// Discovered on first use (retried on failure) so startup does not depend on the IdP.
+    metadata: OnceCell<ProviderMetadata>,
+}
+
+impl OidcProvider {
+    /// Consent URL for the authorization code flow with PKCE (S256).
+    pub async fn authorization_url(&self, state: &str, nonce: &str, code_challenge: &str) -> Result<String, ApiError> {
+        let metadata = self.metadata().await?;
+        let separator = if metadata.authorization_endpoint.contains('?') { '&' } else { '?' };
+        let mut url = format!(
+            "{}{}client_id={}&redirect_uri={}&response_type=code&scope={}&state={}",
+            metadata.authorization_endpoint,
+            separator,
+            urlencoding::encode(&self.config.client_id),
+            urlencoding::encode(&self.config.redirect_uri),
+            urlencoding::encode(&self.config.scopes),
+            urlencoding::encode(state)
+        );
+        if self.config.name == "google" {
+            url.push_str("&access_type=offline&prompt=consent");
+        }
+        if let Some(mode) = self.config.response_mode.as_deref() {
+            url.push_str(&format!("&response_mode={}", urlencoding::encode(mode)));
+        }
+        url.push_str(&format!(
+            "&nonce={}&code_challenge={}&code_challenge_method=S256",
+            urlencoding::encode(nonce),
+            urlencoding::encode(code_challenge)
+        ));
+        Ok(url)
+    }
+
+    /// Exchanges an authorization code and resolves the user's profile: from the ID token when the
+    /// provider returns one, completed by (or, without ID token, taken from) the userinfo endpoint.
+    pub async fn user_from_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: Option<&str>,
+    ) -> Result<ProviderUserInfo, ApiError> {
+        let token = self.exchange_code(code, code_verifier).await?;
+        let metadata = self.metadata().await?;
+
+        let from_id_token = match token.id_token.as_deref() {
+            Some(id_token) if metadata.jwks.is_some() => Some(self.verify_id_token(id_token, nonce).await?),
+            _ => None,
+        };
+
+        match (from_id_token, metadata.userinfo_endpoint.is_some()) {
+            (Some(info), _) if !info.email.is_empty() => Ok(info),
+            (Some(info), true) => {
+                // Same account required: a userinfo response for another subject is ignored.
+                let userinfo = self.fetch_userinfo(&token.access_token).await?;
+                if userinfo.sub != info.sub {
+                    return Err(ApiError::Unauthorized);
+                }
+                Ok(userinfo)
+            }
+            (Some(info), false) => Ok(info),
+            (None, true) => self.fetch_userinfo(&token.access_token).await,
+            (None, false) => {
+                tracing::warn!(provider = %self.config.name, "oidc.no_identity_source");
+                Err(ApiError::Unauthorized)
+            }
+        }
+    }
+
+    /// Verifies an ID token against the provider's JWKS: signature, `aud` (client id or an extra
+    /// audience), `iss`, `exp`, and `nonce` when either side has one.
+    pub async fn verify_id_token(&self, id_token: &str, nonce: Option<&str>) -> Result<ProviderUserInfo, ApiError> {
+        #[derive(Deserialize)]
+        struct IdTokenClaims {
+            iss: String,
+            tid: Option<String>,
+            nonce: Option<String>,
+            #[serde(flatten)]
+            user: ProviderUserInfo,
+        }
+
+        let metadata = self.metadata().await?;
+        let jwks = metadata.jwks.as_ref().ok_or(ApiError::Unauthorized)?;
+
+        let mut audiences = vec![self.config.client_id.as_str()];
+        audiences.extend(self.config.extra_audiences.iter().map(String::as_str));
+        // Multi-tenant issuers (Microsoft "common") are templated: checked below against `tid`.
+        let templated = metadata.issuer.contains("{tenantid}");
+        let issuers = if templated { Vec::new() } else { accepted_issuers(&metadata.issuer) };
+
+        let claims: IdTokenClaims = jwks.verify(id_token, &audiences, &issuers).await?;
+
+        if templated {
+            let expected = claims.tid.as_deref().map(|tid| metadata.issuer.replace("{tenantid}", tid));
+            if expected.as_deref() != Some(claims.iss.as_str()) {
+                tracing::warn!(provider = %self.config.name, iss = %claims.iss, "oidc.id_token.issuer_mismatch");
+                return Err(ApiError::Unauthorized);
+            }
+        }
+
+        let nonce_ok = match (claims.nonce.as_deref(), nonce) {
+            (None, None) => true,
+            (Some(got), Some(expected)) => got == expected,
+            _ => false,
+        };
+        if !nonce_ok {
+            tracing::warn!(provider = %self.config.name, "oidc.id_token.nonce_mismatch");
+            return Err(ApiError::Unauthorized);
+        }
+
+        Ok(claims.user)
+    }
+
+    async fn exchange_code(&self, code: &str, code_verifier: &str) -> Result<TokenResponse, ApiError> {
+        #[derive(Serialize)]
+        struct Body<'a> {
+            code: &'a str,
+            client_id: &'a str,
+            client_secret: &'a str,
+            redirect_uri: &'a str,
+            grant_type: &'a str,
+            code_verifier: &'a str,
+        }
+        let body = Body {
+            code,
+            client_id: &self.config.client_id,
+            client_secret: &self.config.client_secret,
+            redirect_uri: &self.config.redirect_uri,
+            grant_type: "authorization_code",
+            code_verifier,
+        };
+
+        let metadata = self.metadata().await?;
+        let res = self
+            .http
+            .post(&metadata.token_endpoint)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .form(&body)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!(provider = %self.config.name, error = ?e, "oidc.token.request_failed");
+                ApiError::Internal
+            })?;
+
+        if !res.status().is_success() {
+            let status = res.status();
+            let text = res.text().await.unwrap_or_default();
+            tracing::warn!(provider = %self.config.name, status = %status, body = %text, "oidc.token.exchange_failed");
+            return Err(ApiError::Unauthorized);
+        }
+
+        res.json::<TokenResponse>().await.map_err(|e| {
+            tracing::error!(provider = %self.config.name, error = ?e, "oidc.token.parse_failed");
+            ApiError::Internal
+        })
+    }
+
+    async fn fetch_userinfo(&self, access_token: &str) -> Result<ProviderUserInfo, ApiError> {
+        let metadata = self.metadata().await?;
+        let endpoint = metadata.userinfo_endpoint.as_deref().ok_or(ApiError::Unauthorized)?;
+        let res = self
+            .http
+            .get(endpoint)
+            .bearer_auth(access_token)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!(provider = %self.config.name, error = ?e, "oidc.userinfo.request_failed");
+                ApiError::Internal
+            })?;
+
+        if !res.status().is_success() {
+            let status = res.status();
+            let text = res.text().await.unwrap_or_default();
+            tracing::warn!(provider = %self.config.name, status = %status, body = %text, "oidc.userinfo_failed");
+            return Err(ApiError::Unauthorized);
+        }
+
+        res.json::<ProviderUserInfo>().await.map_err(|e| {
+            tracing::error!(provider = %self.config.name, error = ?e, "oidc.userinfo.parse_failed");
+            ApiError::Internal
+        })
+    }
+
+    async fn metadata(&self) -> Result<&ProviderMetadata, ApiError> {
+        self.metadata.get_or_try_init(|| self.discover()).await
+    }
+
+    async fn discover(&self) -> Result<ProviderMetadata, ApiError> {
+        #[derive(Deserialize, Default)]
+        struct Discovery {
+            issuer: Option<String>,
+            authorization_endpoint: Option<String>,
+            token_endpoint: Option<String>,
+            userinfo_endpoint: Option<String>,
+            jwks_uri: Option<String>,
+        }
+
+        let config = &self.config;
+        let fully_overridden = config.authorization_endpoint.is_some()
+            && config.token_endpoint.is_some()
+            && (config.userinfo_endpoint.is_some() || config.jwks_uri.is_some());
+
+        let discovery = if fully_overridden {
+            Discovery::default()
+        } else {
+            let url = config.discovery_url.clone().unwrap_or_else(|| {
+                format!("{}/.well-known/openid-configuration", config.issuer.trim_end_matches('/'))
+            });
+            let res = self.http.get(&url).send().await.map_err(|e| {
+                tracing::error!(provider = %config.name, url = %url, error = ?e, "oidc.discovery.request_failed");
+                ApiError::ServiceUnavailable
+            })?;
+            if !res.status().is_success() {
+                tracing::error!(provider = %config.name, url = %url, status = %res.status(), "oidc.discovery.failed");
+                return Err(ApiError::ServiceUnavailable);
+            }
+            res.json::<Discovery>().await.map_err(|e| {
+                tracing::error!(provider = %config.name, url = %url, error = ?e, "oidc.discovery.parse_failed");
+                ApiError::ServiceUnavailable
+            })?
+        };
+
+        let missing = |what: &str| {
+            tracing::error!(provider = %config.name, endpoint = %what, "oidc.discovery.missing_endpoint");
+            ApiError::ServiceUnavailable
+        };
+        let metadata = ProviderMetadata {
+            issuer: discovery.issuer.unwrap_or_else(|| config.issuer.clone()),
+            authorization_endpoint: config
+                .authorization_endpoint
+                .clone()
+                .or(discovery.authorization_endpoint)
+                .ok_or_else(|| missing("authorization_endpoint"))?,
+            token_endpoint: config
+                .token_endpoint
+                .clone()
+                .or(discovery.token_endpoint)
+                .ok_or_else(|| missing("token_endpoint"))?,
+            userinfo_endpoint: config.userinfo_endpoint.clone().or(discovery.userinfo_endpoint),
+            jwks: config
+                .jwks_uri
+                .clone()
+                .or(discovery.jwks_uri)
+                .map(|uri| RemoteJwks::new(uri, self.http.clone())),
+        };
+
+        tracing::info!(provider = %config.name, issuer = %metadata.issuer, "oidc.discovery.loaded");
+        Ok(metadata)
+    }
+}
+
+/// Google also issues ID tokens with the scheme-less `accounts.google.com` issuer.
+fn accepted_issuers(issuer: &str) -> Vec<&str> {
+    match issuer.strip_prefix("https://") {
+        Some(bare @ "accounts.google.com") => vec![issuer, bare],
+        _ => vec![issuer],
+    }
+}
+
+/// `email_verified` is a boolean for most providers but a string ("true") for some (Apple, Google tokeninfo).
+fn bool_or_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum BoolOrString {
+        Bool(bool),
+        String(String),
+    }
+    Ok(match Option::<BoolOrString>::deserialize(deserializer)? {
+        Some(BoolOrString::Bool(b)) => b,
+        Some(BoolOrString::String(s)) => s == "true",
+        None => false,
+    })
+}
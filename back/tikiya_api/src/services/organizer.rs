@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::dto::{OrganizerApplicationRequest, OrganizerApplicationResponse, SupportingDocument};
+use crate::error::ApiError;
+use crate::models::Role;
+use crate::security::phone::normalize_e164;
+use crate::security::client_info::ClientInfo;
+use crate::services::audit::{self, AuditEvent, Outcome};
+use crate::services::mail::Email;
+use crate::state::AppState;
+
+const APPLICATION_COLUMNS: &str = "id, user_id, organization_name, legal_id, contact_name, contact_email, contact_phone, status, review_note, reviewed_at, organization_id, created_at";
+const LIST_LIMIT: i64 = 200;
+
+#[derive(sqlx::FromRow)]
+struct ApplicationRow {
+    id: Uuid,
+    user_id: Uuid,
+    organization_name: String,
+    legal_id: String,
+    contact_name: Option<String>,
+    contact_email: Option<String>,
+    contact_phone: Option<String>,
+    status: String,
+    review_note: Option<String>,
+    reviewed_at: Option<DateTime<Utc>>,
+    organization_id: Option<Uuid>,
+    created_at: DateTime<Utc>,
+}
+
+impl ApplicationRow {
+    fn into_response(self, documents: Vec<SupportingDocument>) -> OrganizerApplicationResponse {
+        OrganizerApplicationResponse {
+            id: self.id,
+            user_id: self.user_id,
+            organization_name: self.organization_name,
+            legal_id: self.legal_id,
+            contact_name: self.contact_name,
+            contact_email: self.contact_email,
+            contact_phone: self.contact_phone,
+            documents,
+            status: self.status,
+            review_note: self.review_note,
+            reviewed_at: self.reviewed_at,
+            organization_id: self.organization_id,
+            created_at: self.created_at,
+        }
+    }
+}
+
+pub struct OrganizerService {
+    state: AppState,
+}
+
+impl OrganizerService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    /// Files an application. A user has at most one pending application, and organizers and
+    /// admins cannot apply.
+    pub async fn apply(&self, user_id: Uuid, role: Role, payload: OrganizerApplicationRequest) -> Result<OrganizerApplicationResponse, ApiError> {
+        if role != Role::Client {
+            return Err(ApiError::Conflict("already an organizer".into()));
+        }
+        let contact_phone = normalize_e164(&payload.contact_phone, &self.state.config.phone_default_country_code)?;
+
+        let mut tx = self.state.db.pool.begin().await?;
+        let row = sqlx::query_as::<_, ApplicationRow>(&format!(
+            "INSERT INTO organizer_applications (user_id, organization_name, legal_id, contact_name, contact_email, contact_phone) \
+             VALUES ($1, $2, $3, $4, $5, $6) RETURNING {APPLICATION_COLUMNS}"
+        ))
+        .bind(user_id)
+        .bind(payload.organization_name.trim())
+        .bind(payload.legal_id.trim())
+        .bind(payload.contact_name.trim())
+        .bind(payload.contact_email.trim())
+        .bind(&contact_phone)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|err| match ApiError::from(err) {
+            ApiError::Conflict(_) => ApiError::Conflict("an application is already pending".into()),
+            other => other,
+        })?;
+
+        for document in &payload.documents {
+            sqlx::query("INSERT INTO organizer_application_documents (application_id, name, url) VALUES ($1, $2, $3)")
+                .bind(row.id)
+                .bind(document.name.trim())
+                .bind(&document.url)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        tracing::info!(user_id = %user_id, application_id = %row.id, "organizer.application.submitted");
+        Ok(row.into_response(payload.documents))
+    }
+
+    /// The caller's most recent application.
+    pub async fn latest_for_user(&self, user_id: Uuid) -> Result<OrganizerApplicationResponse, ApiError> {
+        let row = sqlx::query_as::<_, ApplicationRow>(&format!(
+            "SELECT {APPLICATION_COLUMNS} FROM organizer_applications WHERE user_id = $1 ORDER BY created_at DESC LIMIT 1"
+        ))
+        .bind(user_id)
+        .fetch_optional(&self.state.db.pool)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+        let mut documents = self.documents(&[row.id]).await?;
+        let docs = documents.remove(&row.id).unwrap_or_default();
+        Ok(row.into_response(docs))
+    }
+
+    /// Every application of the user, oldest first (data export).
+    pub async fn list_for_user(&self, user_id: Uuid) -> Result<Vec<OrganizerApplicationResponse>, ApiError> {
+        let rows = sqlx::query_as::<_, ApplicationRow>(&format!(
+            "SELECT {APPLICATION_COLUMNS} FROM organizer_applications WHERE user_id = $1 ORDER BY created_at"
+        ))
+        .bind(user_id)
+        .fetch_all(&self.state.db.pool)
+        .await?;
+
+        let ids: Vec<Uuid> = rows.iter().map(|row| row.id).collect();
+        let mut documents = self.documents(&ids).await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let docs = documents.remove(&row.id).unwrap_or_default();
+                row.into_response(docs)
+            })
+            .collect())
+    }
+
+    /// Applications with the given status (all when `None`), oldest first.
+    pub async fn list(&self, status: Option<&str>) -> Result<Vec<OrganizerApplicationResponse>, ApiError> {
+        let rows = sqlx::query_as::<_, ApplicationRow>(&format!(
+            "SELECT {APPLICATION_COLUMNS} FROM organizer_applications WHERE ($1::text IS NULL OR status = $1) ORDER BY created_at LIMIT $2"
+        ))
+        .bind(status)
+        .bind(LIST_LIMIT)
+        .fetch_all(&self.state.db.pool)
+        .await?;
+
+        let ids: Vec<Uuid> = rows.iter().map(|row| row.id).collect();
+        let mut documents = self.documents(&ids).await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let docs = documents.remove(&row.id).unwrap_or_default();
+                row.into_response(docs)
+            })
+            .collect())
+    }
+
+    /// Creates the organization, makes the applicant an organizer and closes the application.
+    pub async fn approve(&self, admin_id: Uuid, client: &ClientInfo, application_id: Uuid, note: Option<&str>) -> Result<OrganizerApplicationResponse, ApiError> {
+        let mut tx = self.state.db.pool.begin().await?;
+        let application = self.lock_pending(&mut tx, application_id).await?;
+
+        let organization_id = sqlx::query_scalar::<_, Uuid>(
+            "INSERT INTO organizations (name, legal_id, contact_email, contact_phone, owner_user_id) VALUES ($1, $2, $3, $4, $5) RETURNING id",
+        )
+        .bind(&application.organization_name)
+        .bind(&application.legal_id)
+        .bind(&application.contact_email)
+        .bind(&application.contact_phone)
+        .bind(application.user_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|err| match ApiError::from(err) {
+            ApiError::Conflict(_) => ApiError::Conflict("an organization with this legal id already exists".into()),
+            other => other,
+        })?;
+
+        // Admins keep their role; the new role reaches the access token on the next refresh.
+        sqlx::query("UPDATE users SET role = 'organizer' WHERE id = $1 AND role = 'client'")
+            .bind(application.user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let row = sqlx::query_as::<_, ApplicationRow>(&format!(
+            "UPDATE organizer_applications SET status = 'approved', review_note = $2, reviewed_by = $3, reviewed_at = NOW(), organization_id = $4 \
+             WHERE id = $1 RETURNING {APPLICATION_COLUMNS}"
+        ))
+        .bind(application_id)
+        .bind(note.map(str::trim).filter(|n| !n.is_empty()))
+        .bind(admin_id)
+        .bind(organization_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let event = AuditEvent::new("admin.organizer_application.approved", Outcome::Success, client)
+            .actor(admin_id)
+            .subject(Some(row.user_id))
+            .details(json!({ "application_id": application_id, "organization_id": organization_id }));
+        audit::record(&mut tx, &event).await?;
+        tx.commit().await?;
+
+        tracing::info!(admin_id = %admin_id, application_id = %application_id, organization_id = %organization_id, "organizer.application.approved");
+        self.notify(
+            row.user_id,
+            "Votre compte organisateur est activé",
+            format!(
+                "Bonjour,\n\nVotre demande pour « {} » a été acceptée. Reconnectez-vous à l'application pour accéder à l'espace organisateur.",
+                row.organization_name
+            ),
+        )
+        .await;
+
+        let mut documents = self.documents(&[row.id]).await?;
+        let docs = documents.remove(&row.id).unwrap_or_default();
+        Ok(row.into_response(docs))
+    }
+
+    pub async fn reject(&self, admin_id: Uuid, client: &ClientInfo, application_id: Uuid, reason: &str) -> Result<OrganizerApplicationResponse, ApiError> {
+        let mut tx = self.state.db.pool.begin().await?;
+        self.lock_pending(&mut tx, application_id).await?;
+
+        let row = sqlx::query_as::<_, ApplicationRow>(&format!(
+            "UPDATE organizer_applications SET status = 'rejected', review_note = $2, reviewed_by = $3, reviewed_at = NOW() \
+             WHERE id = $1 RETURNING {APPLICATION_COLUMNS}"
+        ))
+        .bind(application_id)
+        .bind(reason.trim())
+        .bind(admin_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let event = AuditEvent::new("admin.organizer_application.rejected", Outcome::Success, client)
+            .actor(admin_id)
+            .subject(Some(row.user_id))
+            .details(json!({ "application_id": application_id }));
+        audit::record(&mut tx, &event).await?;
+        tx.commit().await?;
+
+        tracing::info!(admin_id = %admin_id, application_id = %application_id, "organizer.application.rejected");
+        self.notify(
+            row.user_id,
+            "Votre demande organisateur",
+            format!(
+                "Bonjour,\n\nVotre demande pour « {} » n'a pas été acceptée :\n{}\n\nVous pouvez déposer une nouvelle demande depuis l'application.",
+                row.organization_name,
+                reason.trim()
+            ),
+        )
+        .await;
+
+        let mut documents = self.documents(&[row.id]).await?;
+        let docs = documents.remove(&row.id).unwrap_or_default();
+        Ok(row.into_response(docs))
+    }
+
+    async fn lock_pending(&self, tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, application_id: Uuid) -> Result<ApplicationRow, ApiError> {
+        let application = sqlx::query_as::<_, ApplicationRow>(&format!(
+            "SELECT {APPLICATION_COLUMNS} FROM organizer_applications WHERE id = $1 FOR UPDATE"
+        ))
+        .bind(application_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+        if application.status != "pending" {
+            return Err(ApiError::Conflict(format!("application already {}", application.status)));
+        }
+        Ok(application)
+    }
+
+    async fn documents(&self, application_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<SupportingDocument>>, ApiError> {
+        let rows = sqlx::query_as::<_, (Uuid, String, String)>(
+            "SELECT application_id, name, url FROM organizer_application_documents WHERE application_id = ANY($1) ORDER BY created_at",
+        )
+        .bind(application_ids)
+        .fetch_all(&self.state.db.pool)
+        .await?;
+
+        let mut documents: HashMap<Uuid, Vec<SupportingDocument>> = HashMap::new();
+        for (application_id, name, url) in rows {
+            documents.entry(application_id).or_default().push(SupportingDocument { name, url });
+        }
+        Ok(documents)
+    }
+
+    /// Mails the applicant about the decision. Best effort: the decision stands if mail fails.
+    async fn notify(&self, user_id: Uuid, subject: &str, body: String) {
+        let email = sqlx::query_scalar::<_, Option<String>>("SELECT email FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.state.db.pool)
+            .await
+            .ok()
+            .flatten()
+            .flatten();
+        let Some(to) = email else {
+            return;
+        };
+        let result = self
+            .state
+            .mailer
+            .send(Email {
+                to,
+                subject: subject.to_string(),
+                body,
+            })
+            .await;
+        if let Err(err) = result {
+            tracing::error!(user_id = %user_id, error = %err, "organizer.application.mail_failed");
+        }
+    }
+}
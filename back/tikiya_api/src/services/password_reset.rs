@@ -0,0 +1,183 @@
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::dto::ResetPasswordRequest;
+use crate::error::ApiError;
+use crate::models::{User, USER_COLUMNS};
+use crate::security::client_info::ClientInfo;
+use crate::security::tokens::{hmac_token, random_token};
+use crate::services::audit::{self, AuditEvent, Outcome};
+use crate::services::auth::{discard_unproven_credentials, AuthService};
+use crate::services::login_throttle::LoginThrottleService;
+use crate::services::mail::Email;
+use crate::state::AppState;
+
+const RESET_TOKEN_TTL_MINUTES: i64 = 30;
+/// At most this many reset links per account within `RATE_WINDOW_MINUTES`.
+const MAX_LINKS_PER_WINDOW: i64 = 3;
+const RATE_WINDOW_MINUTES: i64 = 15;
+
+pub struct PasswordResetService {
+    state: AppState,
+}
+
+impl PasswordResetService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    /// Mails a reset link if the address belongs to an account. Unknown addresses are a silent no-op:
+    /// the caller always answers 202 so emails cannot be enumerated. Over the per-account limit the
+    /// request is dropped silently too; a new link makes the earlier ones unusable.
+    pub async fn forgot(&self, email: &str) -> Result<(), ApiError> {
+        let Some(user) = sqlx::query_as::<_, User>(&format!("SELECT {USER_COLUMNS} FROM users WHERE email = $1"))
+            .bind(email)
+            .fetch_optional(&self.state.db.pool)
+            .await?
+        else {
+            tracing::info!("auth.password_forgot.unknown_email");
+            return Ok(());
+        };
+
+        let token = random_token(32);
+        let token_hash = hmac_token(&self.state.config.one_time_token_secret, &token)?;
+        let expires_at = Utc::now() + Duration::minutes(RESET_TOKEN_TTL_MINUTES);
+
+        let mut tx = self.state.db.pool.begin().await?;
+        // Serializes concurrent requests for the account, so the count below holds.
+        sqlx::query("SELECT id FROM users WHERE id = $1 FOR UPDATE")
+            .bind(user.id)
+            .execute(&mut *tx)
+            .await?;
+        let recent = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM password_reset_tokens WHERE user_id = $1 AND created_at > $2",
+        )
+        .bind(user.id)
+        .bind(Utc::now() - Duration::minutes(RATE_WINDOW_MINUTES))
+        .fetch_one(&mut *tx)
+        .await?;
+        if recent >= MAX_LINKS_PER_WINDOW {
+            tx.rollback().await?;
+            tracing::warn!(user_id = %user.id, "auth.password_forgot.rate_limited");
+            return Ok(());
+        }
+
+        sqlx::query("UPDATE password_reset_tokens SET consumed_at = NOW() WHERE user_id = $1 AND consumed_at IS NULL")
+            .bind(user.id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("INSERT INTO password_reset_tokens (user_id, token_hash, expires_at) VALUES ($1, $2, $3)")
+            .bind(user.id)
+            .bind(&token_hash)
+            .bind(expires_at)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        let link = format!(
+            "{}/reset-password?token={}",
+            self.state.config.app_public_url,
+            urlencoding::encode(&token)
+        );
+        self.state
+            .mailer
+            .send(Email {
+                to: email.to_string(),
+                subject: "Réinitialisation de votre mot de passe".to_string(),
+                body: format!(
+                    "Bonjour,\n\nPour choisir un nouveau mot de passe Tikiya, ouvrez ce lien :\n{}\n\nCe lien expire dans {} minutes. Si vous n'êtes pas à l'origine de cette demande, ignorez ce message.",
+                    link, RESET_TOKEN_TTL_MINUTES
+                ),
+            })
+            .await?;
+
+        tracing::info!(user_id = %user.id, "auth.password_forgot.sent");
+        Ok(())
+    }
+
+    /// Consumes the token, stores the new password, revokes every session of the user and clears
+    /// its login backoff. When the email was unverified, the account's other credentials are
+    /// discarded as well.
+    pub async fn reset(&self, payload: ResetPasswordRequest, client: &ClientInfo) -> Result<(), ApiError> {
+        let token_hash = hmac_token(&self.state.config.one_time_token_secret, &payload.token)?;
+
+        // Policy check before consuming the token, so a rejected password leaves the link usable.
+        let owner = sqlx::query_as::<_, (Option<String>, Option<String>, Option<String>)>(
+            "SELECT u.email, u.first_name, u.last_name FROM password_reset_tokens t JOIN users u ON u.id = t.user_id \
+             WHERE t.token_hash = $1 AND t.consumed_at IS NULL AND t.expires_at > NOW()",
+        )
+        .bind(&token_hash)
+        .fetch_optional(&self.state.db.pool)
+        .await?;
+        if let Some((email, first_name, last_name)) = &owner {
+            let user_inputs: Vec<&str> = [first_name, last_name].into_iter().filter_map(|v| v.as_deref()).collect();
+            self.state
+                .password_policy
+                .check("new_password", &payload.new_password, email.as_deref(), &user_inputs)?;
+        }
+
+        // Hash before opening the transaction: Argon2 is slow and must not hold a connection.
+        let password_hash = AuthService::new(self.state.clone())
+            .hash_password(&payload.new_password)
+            .await?;
+
+        let mut tx = self.state.db.pool.begin().await?;
+
+        let user_id = sqlx::query_scalar::<_, Uuid>(
+            "UPDATE password_reset_tokens SET consumed_at = NOW() WHERE token_hash = $1 AND consumed_at IS NULL AND expires_at > NOW() RETURNING user_id",
+        )
+        .bind(&token_hash)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| ApiError::Validation("invalid or expired reset token".into()))?;
+
+        // The link reached the mailbox, which also proves ownership of the address. On a first
+        // proof, what whoever registered the account set up is discarded.
+        let (email, email_verified_at) = sqlx::query_as::<_, (Option<String>, Option<DateTime<Utc>>)>(
+            "SELECT email, email_verified_at FROM users WHERE id = $1 FOR UPDATE",
+        )
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        let first_proof = email_verified_at.is_none();
+        if let (true, Some(email)) = (first_proof, email.as_deref()) {
+            discard_unproven_credentials(&mut tx, user_id, email).await?;
+        }
+        sqlx::query(
+            "UPDATE users SET password_hash = $1, failed_attempts = 0, lockout_until = NULL, email_verified_at = COALESCE(email_verified_at, NOW()) WHERE id = $2",
+        )
+        .bind(&password_hash)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        // Other outstanding reset links for this account are now stale.
+        sqlx::query("UPDATE password_reset_tokens SET consumed_at = NOW() WHERE user_id = $1 AND consumed_at IS NULL")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let revoked = sqlx::query(
+            "UPDATE sessions SET revoked_at = NOW(), revoked_reason = 'password_reset' WHERE user_id = $1 AND revoked_at IS NULL",
+        )
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        let event = AuditEvent::new("auth.password.reset", Outcome::Success, client)
+            .user(user_id)
+            .details(json!({ "email_verified": first_proof, "revoked_sessions": revoked }));
+        audit::record(&mut tx, &event).await?;
+        tx.commit().await?;
+
+        // The new password starts with a clean login backoff, as after an admin unlock.
+        if let Some(email) = email.as_deref() {
+            LoginThrottleService::new(self.state.clone()).clear_account(email).await?;
+        }
+
+        tracing::info!(user_id = %user_id, revoked_sessions = revoked, "auth.password_reset.success");
+        Ok(())
+    }
+}
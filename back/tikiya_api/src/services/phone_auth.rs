@@ -0,0 +1,196 @@
+use std::net::IpAddr;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::dto::LoginResponse;
+use crate::error::ApiError;
+use crate::models::{User, USER_COLUMNS};
+use crate::security::client_info::ClientInfo;
+use crate::security::phone::normalize_e164;
+use crate::security::tokens::hmac_token;
+use crate::services::auth::AuthService;
+use crate::services::login_throttle::LoginThrottleService;
+use crate::services::sms::Sms;
+use crate::state::AppState;
+
+const CODE_TTL_MINUTES: i64 = 5;
+/// Wrong guesses allowed per code before it is burnt.
+const CODE_MAX_ATTEMPTS: i32 = 5;
+/// At most this many codes per number within `RATE_WINDOW_MINUTES`.
+const MAX_CODES_PER_WINDOW: i64 = 3;
+const RATE_WINDOW_MINUTES: i64 = 15;
+/// Daily ceiling per number: with `CODE_MAX_ATTEMPTS`, at most 50 guesses a day.
+const MAX_CODES_PER_DAY: i64 = 10;
+/// Codes one client IP may request per hour, whatever the numbers.
+const MAX_CODES_PER_IP_PER_HOUR: i64 = 10;
+
+pub struct PhoneAuthService {
+    state: AppState,
+}
+
+impl PhoneAuthService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    pub fn normalize(&self, phone: &str) -> Result<String, ApiError> {
+        normalize_e164(phone, &self.state.config.phone_default_country_code)
+    }
+
+    /// Texts a 6-digit sign-in code to `phone` (E.164), replacing any pending one. Requests over
+    /// the per-number (15 minutes and daily), per-IP or global hourly limit are dropped silently:
+    /// the caller always answers 202.
+    pub async fn send_code(&self, phone: &str, ip: Option<IpAddr>) -> Result<(), ApiError> {
+        let ip = ip.map(|ip| ip.to_string());
+        let now = Utc::now();
+        let mut tx = self.state.db.pool.begin().await?;
+        // Serializes the limit checks with the insert, so concurrent requests cannot all pass them.
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('phone_otps'))")
+            .execute(&mut *tx)
+            .await?;
+        let (number_window, number_day, ip_hour, global_hour) = sqlx::query_as::<_, (i64, i64, i64, i64)>(
+            "SELECT \
+                COUNT(*) FILTER (WHERE phone = $1 AND created_at > $3), \
+                COUNT(*) FILTER (WHERE phone = $1), \
+                COUNT(*) FILTER (WHERE ip IS NOT DISTINCT FROM $2 AND created_at > $4), \
+                COUNT(*) FILTER (WHERE created_at > $4) \
+             FROM phone_otps WHERE created_at > $5",
+        )
+        .bind(phone)
+        .bind(&ip)
+        .bind(now - Duration::minutes(RATE_WINDOW_MINUTES))
+        .bind(now - Duration::hours(1))
+        .bind(now - Duration::days(1))
+        .fetch_one(&mut *tx)
+        .await?;
+        if global_hour >= self.state.config.sms_hourly_limit {
+            tracing::error!(sent_last_hour = global_hour, "auth.phone.global_limit_reached");
+            return Ok(());
+        }
+        let limit = if number_window >= MAX_CODES_PER_WINDOW {
+            Some("number")
+        } else if number_day >= MAX_CODES_PER_DAY {
+            Some("number_daily")
+        } else if ip_hour >= MAX_CODES_PER_IP_PER_HOUR {
+            Some("ip")
+        } else {
+            None
+        };
+        if let Some(limit) = limit {
+            tracing::warn!(limit, ip = ?ip, "auth.phone.rate_limited");
+            return Ok(());
+        }
+
+        let code = random_code();
+        let code_hash = self.hash_code(phone, &code)?;
+        let expires_at = Utc::now() + Duration::minutes(CODE_TTL_MINUTES);
+
+        // One live code per number, so attempt limits cannot be multiplied by requesting more.
+        sqlx::query("UPDATE phone_otps SET consumed_at = NOW() WHERE phone = $1 AND consumed_at IS NULL")
+            .bind(phone)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("INSERT INTO phone_otps (phone, code_hash, ip, expires_at) VALUES ($1, $2, $3, $4)")
+            .bind(phone)
+            .bind(&code_hash)
+            .bind(&ip)
+            .bind(expires_at)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        self.state
+            .sms
+            .send(Sms {
+                to: phone.to_string(),
+                body: format!("Tikiya : votre code de connexion est {code}. Il expire dans {CODE_TTL_MINUTES} minutes."),
+            })
+            .await?;
+
+        tracing::info!("auth.phone.code_sent");
+        Ok(())
+    }
+
+    /// Checks the code and signs the owner of the number in, creating the account on first use.
+    /// Wrong codes also count against the login throttle, per (IP, number) and per IP, so one
+    /// client cannot spread guesses over many numbers.
+    pub async fn verify(&self, phone: &str, code: &str, client: &ClientInfo) -> Result<LoginResponse, ApiError> {
+        let invalid = || ApiError::Validation("invalid or expired code".into());
+        let throttle = LoginThrottleService::new(self.state.clone());
+        throttle.check(client.ip, phone).await?;
+        let mut tx = self.state.db.pool.begin().await?;
+
+        let (otp_id, code_hash, attempts) = sqlx::query_as::<_, (Uuid, String, i32)>(
+            "SELECT id, code_hash, attempts FROM phone_otps WHERE phone = $1 AND consumed_at IS NULL AND expires_at > NOW() ORDER BY created_at DESC LIMIT 1 FOR UPDATE",
+        )
+        .bind(phone)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(invalid)?;
+
+        if self.hash_code(phone, code)? != code_hash {
+            let attempts = attempts + 1;
+            sqlx::query("UPDATE phone_otps SET attempts = $1, consumed_at = CASE WHEN $1 >= $2 THEN NOW() ELSE NULL END WHERE id = $3")
+                .bind(attempts)
+                .bind(CODE_MAX_ATTEMPTS)
+                .bind(otp_id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            tracing::warn!(attempts, ip = ?client.ip, "auth.phone.invalid_code");
+            throttle.record_failure(client.ip, phone).await?;
+            return Err(invalid());
+        }
+
+        sqlx::query("UPDATE phone_otps SET consumed_at = NOW() WHERE id = $1")
+            .bind(otp_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let existing = sqlx::query_as::<_, User>(&format!(
+            "SELECT {USER_COLUMNS} FROM users WHERE phone = $1 AND phone_verified_at IS NOT NULL"
+        ))
+        .bind(phone)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let user = match existing {
+            Some(user) => user,
+            None => {
+                let user = sqlx::query_as::<_, User>(&format!(
+                    "INSERT INTO users (phone, phone_verified_at, role) VALUES ($1, NOW(), 'client') RETURNING {USER_COLUMNS}"
+                ))
+                .bind(phone)
+                .fetch_one(&mut *tx)
+                .await?;
+                tracing::info!(user_id = %user.id, "auth.phone.user_created");
+                user
+            }
+        };
+
+        tx.commit().await?;
+        throttle.record_success(client.ip, phone).await?;
+
+        tracing::info!(user_id = %user.id, "auth.phone.verified");
+        AuthService::new(self.state.clone()).complete_login(&user, client, "phone", false).await
+    }
+
+    /// Codes are only 6 digits: keyed by number so a leaked hash table cannot be brute-forced
+    /// without the server secret.
+    fn hash_code(&self, phone: &str, code: &str) -> Result<String, ApiError> {
+        hmac_token(&self.state.config.one_time_token_secret, &format!("{phone}:{code}"))
+    }
+}
+
+/// Uniform 6-digit code (rejection sampling avoids modulo bias).
+fn random_code() -> String {
+    const LIMIT: u32 = u32::MAX - u32::MAX % 1_000_000;
+    loop {
+        let n = OsRng.next_u32();
+        if n < LIMIT {
+            return format!("{:06}", n % 1_000_000);
+        }
+    }
+}
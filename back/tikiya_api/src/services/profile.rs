@@ -0,0 +1,70 @@
+use uuid::Uuid;
+
+use crate::dto::{UpdateProfileRequest, UserResponse};
+use crate::error::ApiError;
+use crate::models::{User, USER_COLUMNS};
+use crate::security::phone::normalize_e164;
+use crate::state::AppState;
+
+pub struct ProfileService {
+    state: AppState,
+}
+
+impl ProfileService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    pub async fn get(&self, user_id: Uuid) -> Result<UserResponse, ApiError> {
+        let user = sqlx::query_as::<_, User>(&format!("SELECT {USER_COLUMNS} FROM users WHERE id = $1"))
+            .bind(user_id)
+            .fetch_optional(&self.state.db.pool)
+            .await?
+            .ok_or(ApiError::Unauthorized)?;
+
+        Ok(UserResponse::from(&user))
+    }
+
+    pub async fn update(&self, user_id: Uuid, mut payload: UpdateProfileRequest) -> Result<UserResponse, ApiError> {
+        if let Some(phone) = payload.phone.as_deref().filter(|p| !p.trim().is_empty()) {
+            payload.phone = Some(normalize_e164(phone, &self.state.config.phone_default_country_code)?);
+        }
+        if payload.phone.is_some() {
+            // The number of a phone-only account is its sign-in: changing it would lock the user out.
+            let (email, phone) = sqlx::query_as::<_, (Option<String>, Option<String>)>("SELECT email, phone FROM users WHERE id = $1")
+                .bind(user_id)
+                .fetch_optional(&self.state.db.pool)
+                .await?
+                .ok_or(ApiError::Unauthorized)?;
+            if email.is_none() && payload.phone.as_deref().map(str::trim) != phone.as_deref() {
+                return Err(ApiError::Validation("phone: this number signs in to the account and cannot be changed".into()));
+            }
+        }
+
+        // NULL parameter = keep current value; blank string = clear the column. A new number
+        // is unverified until confirmed by SMS code.
+        let user = sqlx::query_as::<_, User>(&format!(
+            "UPDATE users SET \
+                first_name = CASE WHEN $2::text IS NULL THEN first_name ELSE NULLIF(btrim($2), '') END, \
+                last_name = CASE WHEN $3::text IS NULL THEN last_name ELSE NULLIF(btrim($3), '') END, \
+                phone = CASE WHEN $4::text IS NULL THEN phone ELSE NULLIF(btrim($4), '') END, \
+                phone_verified_at = CASE WHEN $4::text IS NULL OR NULLIF(btrim($4), '') IS NOT DISTINCT FROM phone THEN phone_verified_at ELSE NULL END, \
+                preferred_language = CASE WHEN $5::text IS NULL THEN preferred_language ELSE NULLIF(btrim($5), '') END, \
+                city = CASE WHEN $6::text IS NULL THEN city ELSE NULLIF(btrim($6), '') END \
+             WHERE id = $1 RETURNING {USER_COLUMNS}"
+        ))
+        .bind(user_id)
+        .bind(payload.first_name)
+        .bind(payload.last_name)
+        .bind(payload.phone)
+        .bind(payload.preferred_language)
+        .bind(payload.city)
+        .fetch_optional(&self.state.db.pool)
+        .await?
+        .ok_or(ApiError::Unauthorized)?;
+
+        tracing::info!(user_id = %user.id, "profile.update.success");
+
+        Ok(UserResponse::from(&user))
+    }
+}
@@ -0,0 +1,87 @@
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::dto::ReauthRequest;
+use crate::error::{ApiError, FieldError};
+use crate::security::auth_user::AuthUser;
+use crate::security::client_info::ClientInfo;
+use crate::services::auth::AuthService;
+use crate::services::login_throttle::LoginThrottleService;
+use crate::services::mfa::MfaService;
+use crate::state::AppState;
+
+/// A sign-in this recent stands in for a password on password-less accounts.
+const RECENT_SIGN_IN_MINUTES: i64 = 10;
+
+/// Step-up check before changes that outlive the session (passkey enrollment, a first password,
+/// account deletion), so a stolen access token is not enough to make them.
+pub struct ReauthService {
+    state: AppState,
+}
+
+impl ReauthService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    /// Accounts with a password must give it. Password-less accounts (OAuth, magic link, phone)
+    /// give a TOTP or recovery code, or call from a session opened in the last
+    /// `RECENT_SIGN_IN_MINUTES`. Wrong passwords and codes count against the login throttle (codes
+    /// per user id, as on every second-factor route).
+    pub async fn verify(&self, user: &AuthUser, client: &ClientInfo, proof: &ReauthRequest) -> Result<(), ApiError> {
+        let password_hash = sqlx::query_scalar::<_, Option<String>>("SELECT password_hash FROM users WHERE id = $1")
+            .bind(user.id)
+            .fetch_optional(&self.state.db.pool)
+            .await?
+            .ok_or(ApiError::Unauthorized)?;
+        if let Some(hash) = password_hash.as_deref() {
+            let throttle = LoginThrottleService::new(self.state.clone());
+            let throttle_key = user.email.clone().unwrap_or_else(|| user.id.to_string());
+            let current = proof.current_password.as_deref().ok_or_else(|| {
+                ApiError::InvalidFields(vec![FieldError::new("current_password", "required", "is required")])
+            })?;
+            throttle.check(client.ip, &throttle_key).await?;
+            if !AuthService::new(self.state.clone()).verify_password(hash, current).await? {
+                tracing::warn!(user_id = %user.id, "auth.reauth.invalid_password");
+                throttle.record_failure(client.ip, &throttle_key).await?;
+                return Err(ApiError::InvalidFields(vec![FieldError::new(
+                    "current_password",
+                    "incorrect",
+                    "is incorrect",
+                )]));
+            }
+            throttle.record_success(client.ip, &throttle_key).await?;
+            return Ok(());
+        }
+
+        if proof.mfa.code.is_some() || proof.mfa.recovery_code.is_some() {
+            let mfa = MfaService::new(self.state.clone());
+            if !mfa.check_second_factor(user.id, &user.account_name(), &proof.mfa, client).await? {
+                tracing::warn!(user_id = %user.id, "auth.reauth.invalid_code");
+                let field = if proof.mfa.code.is_some() { "code" } else { "recovery_code" };
+                return Err(ApiError::InvalidFields(vec![FieldError::new(field, "incorrect", "is incorrect")]));
+            }
+            return Ok(());
+        }
+
+        if self.signed_in_recently(user.id, user.session_id).await? {
+            return Ok(());
+        }
+        tracing::info!(user_id = %user.id, "auth.reauth.required");
+        Err(ApiError::ReauthenticationRequired)
+    }
+
+    async fn signed_in_recently(&self, user_id: Uuid, session_id: Option<Uuid>) -> Result<bool, ApiError> {
+        let Some(session_id) = session_id else {
+            return Ok(false);
+        };
+        let created_at = sqlx::query_scalar::<_, DateTime<Utc>>(
+            "SELECT created_at FROM sessions WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL",
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .fetch_optional(&self.state.db.pool)
+        .await?;
+        Ok(created_at.is_some_and(|at| at > Utc::now() - Duration::minutes(RECENT_SIGN_IN_MINUTES)))
+    }
+}
@@ -0,0 +1,99 @@
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::dto::SessionResponse;
+use crate::error::ApiError;
+use crate::security::client_info::ClientInfo;
+use crate::services::audit::{self, AuditEvent, Outcome};
+use crate::state::AppState;
+
+pub struct SessionService {
+    state: AppState,
+}
+
+impl SessionService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    /// Active (not revoked, not expired) sessions of the user, most recently used first.
+    pub async fn list(&self, user_id: Uuid, current: Option<Uuid>) -> Result<Vec<SessionResponse>, ApiError> {
+        #[derive(sqlx::FromRow)]
+        struct SessionRow {
+            id: Uuid,
+            device_label: Option<String>,
+            user_agent: Option<String>,
+            ip: Option<String>,
+            created_at: DateTime<Utc>,
+            last_used_at: Option<DateTime<Utc>>,
+            expires_at: DateTime<Utc>,
+        }
+
+        let rows = sqlx::query_as::<_, SessionRow>(
+            "SELECT id, device_label, user_agent, ip, created_at, last_used_at, expires_at FROM sessions WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW() ORDER BY COALESCE(last_used_at, created_at) DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.state.db.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| SessionResponse {
+                id: row.id,
+                device_label: row.device_label,
+                user_agent: row.user_agent,
+                ip: row.ip,
+                created_at: row.created_at,
+                last_used_at: row.last_used_at,
+                expires_at: row.expires_at,
+                current: Some(row.id) == current,
+            })
+            .collect())
+    }
+
+    pub async fn revoke(&self, user_id: Uuid, session_id: Uuid, client: &ClientInfo) -> Result<(), ApiError> {
+        let revoked = sqlx::query("UPDATE sessions SET revoked_at = NOW() WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL")
+            .bind(session_id)
+            .bind(user_id)
+            .execute(&self.state.db.pool)
+            .await?
+            .rows_affected();
+
+        if revoked == 0 {
+            return Err(ApiError::NotFound);
+        }
+
+        tracing::info!(user_id = %user_id, session_id = %session_id, "auth.sessions.revoked");
+        let event = AuditEvent::new("auth.sessions.revoked", Outcome::Success, client)
+            .user(user_id)
+            .details(json!({ "session_id": session_id }));
+        audit::record_detached(&self.state, event).await;
+        Ok(())
+    }
+
+    /// [`revoke_all`](Self::revoke_all) at the user's own request, recorded in the audit trail.
+    pub async fn sign_out_all(&self, user_id: Uuid, keep: Option<Uuid>, client: &ClientInfo) -> Result<u64, ApiError> {
+        let revoked = self.revoke_all(user_id, keep).await?;
+        let event = AuditEvent::new("auth.sessions.revoked", Outcome::Success, client)
+            .user(user_id)
+            .details(json!({ "scope": "all", "kept_current": keep.is_some(), "revoked_sessions": revoked }));
+        audit::record_detached(&self.state, event).await;
+        Ok(revoked)
+    }
+
+    /// Revokes every active session of the user, optionally sparing `keep`. Returns how many were revoked.
+    pub async fn revoke_all(&self, user_id: Uuid, keep: Option<Uuid>) -> Result<u64, ApiError> {
+        let revoked = sqlx::query(
+            "UPDATE sessions SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL AND id IS DISTINCT FROM $2",
+        )
+        .bind(user_id)
+        .bind(keep)
+        .execute(&self.state.db.pool)
+        .await?
+        .rows_affected();
+
+        tracing::info!(user_id = %user_id, revoked_sessions = revoked, "auth.sessions.revoked_all");
+        Ok(revoked)
+    }
+}
@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use futures_util::future::BoxFuture;
+
+use crate::config::AppConfig;
+use crate::error::ApiError;
+
+#[derive(Debug, Clone)]
+pub struct Sms {
+    /// E.164 number.
+    pub to: String,
+    pub body: String,
+}
+
+/// Outgoing SMS gateway. Selected with `SMS_TRANSPORT` (`log` or `file`); a provider-backed
+/// implementation plugs in here.
+pub trait SmsSender: Send + Sync {
+    fn send(&self, sms: Sms) -> BoxFuture<'_, Result<(), ApiError>>;
+}
+
+pub fn from_config(config: &AppConfig) -> Arc<dyn SmsSender> {
+    match config.sms_transport.as_str() {
+        "file" => Arc::new(FileSmsSender {
+            dir: PathBuf::from(&config.sms_outbox_dir),
+        }),
+        "log" => Arc::new(LogSmsSender),
+        // Refuse to start rather than guess: a misspelt transport must not leave codes in the logs.
+        other => panic!("SMS_TRANSPORT inconnu: '{}' (log ou file)", other),
+    }
+}
+
+/// Development transport: logs that a message was sent, without its body (sign-in codes).
+/// Use the `file` transport to read the messages.
+pub struct LogSmsSender;
+
+impl SmsSender for LogSmsSender {
+    fn send(&self, sms: Sms) -> BoxFuture<'_, Result<(), ApiError>> {
+        Box::pin(async move {
+            tracing::info!(to = %sms.to, body_len = sms.body.len(), "sms.log.sent");
+            Ok(())
+        })
+    }
+}
+
+/// Writes one `.txt` file per message into an outbox directory (local dev, tests).
+pub struct FileSmsSender {
+    dir: PathBuf,
+}
+
+impl SmsSender for FileSmsSender {
+    fn send(&self, sms: Sms) -> BoxFuture<'_, Result<(), ApiError>> {
+        Box::pin(async move {
+            let path = self
+                .dir
+                .join(format!("{}-{}.txt", chrono::Utc::now().timestamp_millis(), uuid::Uuid::new_v4()));
+            let content = format!("To: {}\n\n{}\n", sms.to, sms.body);
+            tokio::fs::create_dir_all(&self.dir).await.map_err(|e| {
+                tracing::error!(error = ?e, "sms.file.create_dir_failed");
+                ApiError::Internal
+            })?;
+            tokio::fs::write(&path, content).await.map_err(|e| {
+                tracing::error!(error = ?e, "sms.file.write_failed");
+                ApiError::Internal
+            })?;
+            tracing::info!(to = %sms.to, path = %path.display(), "sms.file.sent");
+            Ok(())
+        })
+    }
+}
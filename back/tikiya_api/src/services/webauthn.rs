@@ -0,0 +1,337 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::dto::{
+    LoginResponse, WebauthnCredentialResponse, WebauthnLoginFinishRequest, WebauthnLoginStartRequest,
+    WebauthnOptionsResponse, WebauthnRegisterFinishRequest,
+};
+use crate::error::ApiError;
+use crate::models::{User, USER_COLUMNS};
+use crate::security::client_info::ClientInfo;
+use crate::security::webauthn::{
+    attestation_auth_data, decode_b64url, parse_authenticator_data, parse_client_data, ClientData, CosePublicKey,
+    SUPPORTED_ALGORITHMS,
+};
+use crate::services::audit::{self, AuditEvent, Outcome};
+use crate::services::auth::{non_blank, AuthService};
+use crate::state::AppState;
+
+/// Same lifetime as the OAuth `state` parameter.
+const CHALLENGE_TTL_SECS: i64 = 10 * 60;
+
+pub struct WebauthnService {
+    state: AppState,
+}
+
+impl WebauthnService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    /// Creation options for a new passkey on the caller's account.
+    pub async fn start_registration(&self, user_id: Uuid, email: &str) -> Result<WebauthnOptionsResponse, ApiError> {
+        let challenge = self.create_challenge("register", Some(user_id)).await?;
+        let exclude = self
+            .credential_ids(user_id)
+            .await?
+            .into_iter()
+            .map(|id| json!({ "type": "public-key", "id": id }))
+            .collect::<Vec<_>>();
+
+        Ok(WebauthnOptionsResponse {
+            public_key: json!({
+                "rp": { "id": self.state.config.webauthn_rp_id, "name": self.state.config.webauthn_rp_name },
+                "user": {
+                    "id": URL_SAFE_NO_PAD.encode(user_id.as_bytes()),
+                    "name": email,
+                    "displayName": email,
+                },
+                "challenge": challenge,
+                "pubKeyCredParams": SUPPORTED_ALGORITHMS
+                    .iter()
+                    .map(|alg| json!({ "type": "public-key", "alg": alg }))
+                    .collect::<Vec<_>>(),
+                "timeout": CHALLENGE_TTL_SECS * 1000,
+                "attestation": "none",
+                "excludeCredentials": exclude,
+                "authenticatorSelection": { "residentKey": "preferred", "userVerification": "preferred" },
+            }),
+        })
+    }
+
+    pub async fn finish_registration(
+        &self,
+        user_id: Uuid,
+        payload: WebauthnRegisterFinishRequest,
+        client: &ClientInfo,
+    ) -> Result<WebauthnCredentialResponse, ApiError> {
+        let invalid = || ApiError::Validation("invalid attestation".into());
+
+        let client_data_json = decode_b64url(&payload.credential.response.client_data_json)?;
+        let client_data = parse_client_data(&client_data_json)?;
+        self.check_client_data(&client_data, "webauthn.create")
+            .map_err(|_| invalid())?;
+        let challenge_user = self
+            .consume_challenge(&client_data.challenge, "register")
+            .await?
+            .ok_or_else(invalid)?;
+        if challenge_user != Some(user_id) {
+            return Err(invalid());
+        }
+
+        let auth_data_bytes = attestation_auth_data(&decode_b64url(&payload.credential.response.attestation_object)?)?;
+        let auth_data = parse_authenticator_data(&auth_data_bytes)?;
+        if !auth_data.rp_id_matches(&self.state.config.webauthn_rp_id) || !auth_data.user_present() {
+            return Err(invalid());
+        }
+        let attested = auth_data.attested.as_ref().ok_or_else(invalid)?;
+        let credential_id = URL_SAFE_NO_PAD.encode(&attested.credential_id);
+        if credential_id != payload.credential.id.trim_end_matches('=') {
+            return Err(invalid());
+        }
+        // Reject keys we could not verify at login time.
+        CosePublicKey::from_cbor(&attested.public_key)?;
+
+        #[derive(sqlx::FromRow)]
+        struct CredentialRow {
+            id: Uuid,
+            name: Option<String>,
+            created_at: DateTime<Utc>,
+        }
+
+        let row = sqlx::query_as::<_, CredentialRow>(
+            "INSERT INTO webauthn_credentials (user_id, credential_id, public_key, sign_count, name) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (credential_id) DO NOTHING RETURNING id, name, created_at",
+        )
+        .bind(user_id)
+        .bind(&credential_id)
+        .bind(&attested.public_key)
+        .bind(auth_data.sign_count as i64)
+        .bind(non_blank(payload.name.as_deref()))
+        .fetch_optional(&self.state.db.pool)
+        .await?
+        .ok_or_else(|| ApiError::Conflict("credential already registered".into()))?;
+
+        tracing::info!(user_id = %user_id, credential = %row.id, "auth.webauthn.registered");
+        let event = AuditEvent::new("auth.passkey.added", Outcome::Success, client)
+            .user(user_id)
+            .details(json!({ "credential": row.id }));
+        audit::record_detached(&self.state, event).await;
+
+        Ok(WebauthnCredentialResponse {
+            id: row.id,
+            name: row.name,
+            created_at: row.created_at,
+            last_used_at: None,
+        })
+    }
+
+    /// Request options for a passkey login. With an email, only that account's credentials are
+    /// allowed; an unknown email gets an empty list, as if the account had no passkey.
+    pub async fn start_login(&self, payload: WebauthnLoginStartRequest) -> Result<WebauthnOptionsResponse, ApiError> {
+        let user_id = match payload.email.as_deref() {
+            Some(email) => sqlx::query_scalar::<_, Uuid>("SELECT id FROM users WHERE email = $1")
+                .bind(email)
+                .fetch_optional(&self.state.db.pool)
+                .await?,
+            None => None,
+        };
+
+        let allow = match user_id {
+            Some(user_id) => self
+                .credential_ids(user_id)
+                .await?
+                .into_iter()
+                .map(|id| json!({ "type": "public-key", "id": id }))
+                .collect::<Vec<_>>(),
+            None => Vec::new(),
+        };
+        let challenge = self.create_challenge("login", user_id).await?;
+
+        Ok(WebauthnOptionsResponse {
+            public_key: json!({
+                "rpId": self.state.config.webauthn_rp_id,
+                "challenge": challenge,
+                "timeout": CHALLENGE_TTL_SECS * 1000,
+                "userVerification": "preferred",
+                "allowCredentials": allow,
+            }),
+        })
+    }
+
+    /// Verifies the assertion, then signs in through [`AuthService::complete_login`]. A passkey
+    /// that verified the user (biometrics / PIN on the device) counts as two factors; without
+    /// user verification, accounts with TOTP get the usual challenge.
+    pub async fn finish_login(&self, payload: WebauthnLoginFinishRequest, client: &ClientInfo) -> Result<LoginResponse, ApiError> {
+        let response = &payload.credential.response;
+        let client_data_json = decode_b64url(&response.client_data_json)?;
+        let client_data = parse_client_data(&client_data_json)?;
+        self.check_client_data(&client_data, "webauthn.get")?;
+        let challenge_user = self
+            .consume_challenge(&client_data.challenge, "login")
+            .await?
+            .ok_or(ApiError::Unauthorized)?;
+
+        #[derive(sqlx::FromRow)]
+        struct CredentialRow {
+            id: Uuid,
+            user_id: Uuid,
+            public_key: Vec<u8>,
+            sign_count: i64,
+        }
+
+        let credential = sqlx::query_as::<_, CredentialRow>(
+            "SELECT id, user_id, public_key, sign_count FROM webauthn_credentials WHERE credential_id = $1",
+        )
+        .bind(payload.credential.id.trim_end_matches('='))
+        .fetch_optional(&self.state.db.pool)
+        .await?
+        .ok_or(ApiError::Unauthorized)?;
+
+        if challenge_user.is_some_and(|id| id != credential.user_id) {
+            return Err(ApiError::Unauthorized);
+        }
+        if let Some(handle) = response.user_handle.as_deref().filter(|h| !h.is_empty()) {
+            if decode_b64url(handle)? != credential.user_id.as_bytes() {
+                return Err(ApiError::Unauthorized);
+            }
+        }
+
+        let auth_data_bytes = decode_b64url(&response.authenticator_data)?;
+        let auth_data = parse_authenticator_data(&auth_data_bytes)?;
+        if !auth_data.rp_id_matches(&self.state.config.webauthn_rp_id) || !auth_data.user_present() {
+            return Err(ApiError::Unauthorized);
+        }
+
+        let key = CosePublicKey::from_cbor(&credential.public_key)?;
+        if !key.verify_assertion(&auth_data_bytes, &client_data_json, &decode_b64url(&response.signature)?) {
+            tracing::warn!(user_id = %credential.user_id, credential = %credential.id, "auth.webauthn.invalid_signature");
+            return Err(ApiError::Unauthorized);
+        }
+
+        // Authenticators that keep a counter must increase it; going backwards hints at a cloned key.
+        let sign_count = auth_data.sign_count as i64;
+        if (sign_count != 0 || credential.sign_count != 0) && sign_count <= credential.sign_count {
+            tracing::warn!(
+                user_id = %credential.user_id,
+                credential = %credential.id,
+                stored = credential.sign_count,
+                received = sign_count,
+                "security.webauthn_sign_count_regression"
+            );
+            return Err(ApiError::Unauthorized);
+        }
+
+        sqlx::query("UPDATE webauthn_credentials SET sign_count = $2, last_used_at = NOW() WHERE id = $1")
+            .bind(credential.id)
+            .bind(sign_count)
+            .execute(&self.state.db.pool)
+            .await?;
+
+        let user = sqlx::query_as::<_, User>(&format!("SELECT {USER_COLUMNS} FROM users WHERE id = $1"))
+            .bind(credential.user_id)
+            .fetch_one(&self.state.db.pool)
+            .await?;
+
+        tracing::info!(user_id = %user.id, user_verified = auth_data.user_verified(), "auth.webauthn.assertion_verified");
+        AuthService::new(self.state.clone())
+            .complete_login(&user, client, "passkey", auth_data.user_verified())
+            .await
+    }
+
+    pub async fn list_credentials(&self, user_id: Uuid) -> Result<Vec<WebauthnCredentialResponse>, ApiError> {
+        #[derive(sqlx::FromRow)]
+        struct CredentialRow {
+            id: Uuid,
+            name: Option<String>,
+            created_at: DateTime<Utc>,
+            last_used_at: Option<DateTime<Utc>>,
+        }
+
+        let rows = sqlx::query_as::<_, CredentialRow>(
+            "SELECT id, name, created_at, last_used_at FROM webauthn_credentials WHERE user_id = $1 ORDER BY created_at",
+        )
+        .bind(user_id)
+        .fetch_all(&self.state.db.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| WebauthnCredentialResponse {
+                id: row.id,
+                name: row.name,
+                created_at: row.created_at,
+                last_used_at: row.last_used_at,
+            })
+            .collect())
+    }
+
+    pub async fn delete_credential(&self, user_id: Uuid, id: Uuid, client: &ClientInfo) -> Result<(), ApiError> {
+        let deleted = sqlx::query("DELETE FROM webauthn_credentials WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.state.db.pool)
+            .await?
+            .rows_affected();
+
+        if deleted == 0 {
+            return Err(ApiError::NotFound);
+        }
+        tracing::info!(user_id = %user_id, credential = %id, "auth.webauthn.credential_deleted");
+        let event = AuditEvent::new("auth.passkey.removed", Outcome::Success, client)
+            .user(user_id)
+            .details(json!({ "credential": id }));
+        audit::record_detached(&self.state, event).await;
+        Ok(())
+    }
+
+    async fn credential_ids(&self, user_id: Uuid) -> Result<Vec<String>, ApiError> {
+        let ids = sqlx::query_scalar::<_, String>("SELECT credential_id FROM webauthn_credentials WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_all(&self.state.db.pool)
+            .await?;
+        Ok(ids)
+    }
+
+    async fn create_challenge(&self, purpose: &str, user_id: Option<Uuid>) -> Result<String, ApiError> {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        let challenge = URL_SAFE_NO_PAD.encode(bytes);
+
+        sqlx::query("INSERT INTO webauthn_challenges (challenge, purpose, user_id, expires_at) VALUES ($1, $2, $3, $4)")
+            .bind(&challenge)
+            .bind(purpose)
+            .bind(user_id)
+            .bind(Utc::now() + Duration::seconds(CHALLENGE_TTL_SECS))
+            .execute(&self.state.db.pool)
+            .await?;
+
+        Ok(challenge)
+    }
+
+    /// Marks the challenge used. `None` when it is unknown, expired or already used; otherwise
+    /// the user it was issued for.
+    async fn consume_challenge(&self, challenge: &str, purpose: &str) -> Result<Option<Option<Uuid>>, ApiError> {
+        let user_id = sqlx::query_scalar::<_, Option<Uuid>>(
+            "UPDATE webauthn_challenges SET consumed_at = NOW() WHERE challenge = $1 AND purpose = $2 AND consumed_at IS NULL AND expires_at > NOW() RETURNING user_id",
+        )
+        .bind(challenge)
+        .bind(purpose)
+        .fetch_optional(&self.state.db.pool)
+        .await?;
+        Ok(user_id)
+    }
+
+    fn check_client_data(&self, client_data: &ClientData, kind: &str) -> Result<(), ApiError> {
+        if client_data.kind != kind {
+            return Err(ApiError::Unauthorized);
+        }
+        if !self.state.config.webauthn_origins.iter().any(|o| o == &client_data.origin) {
+            tracing::warn!(origin = %client_data.origin, "auth.webauthn.origin_mismatch");
+            return Err(ApiError::Unauthorized);
+        }
+        Ok(())
+    }
+}
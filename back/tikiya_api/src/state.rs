@@ -1,8 +1,20 @@
+use std::sync::Arc;
+
 use crate::config::AppConfig;
 use crate::db::Db;
+use crate::security::jwt::JwtKeys;
+use crate::security::password_policy::PasswordPolicy;
+use crate::services::mail::MailSender;
+use crate::services::oidc::OidcRegistry;
+use crate::services::sms::SmsSender;
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: Db,
     pub config: AppConfig,
+    pub mailer: Arc<dyn MailSender>,
+    pub sms: Arc<dyn SmsSender>,
+    pub jwt_keys: Arc<JwtKeys>,
+    pub password_policy: Arc<PasswordPolicy>,
+    pub oidc: Arc<OidcRegistry>,
 }